@@ -366,7 +366,7 @@ fn bench_file_index(c: &mut Criterion) {
 
     for test_suite in test_suites {
         let suite_name = format!("{}->{}", test_suite.file, test_suite.paths.join("->"));
-        let bytes = std::fs::read(&format!("./benches/data/{}.json", test_suite.file)).unwrap();
+        let bytes = std::fs::read(format!("./benches/data/{}.json", test_suite.file)).unwrap();
 
         let value: jsonbb::Value = std::str::from_utf8(&bytes).unwrap().parse().unwrap();
         c.bench_function(&format!("{suite_name} index/jsonbb"), |b| {
@@ -488,6 +488,89 @@ fn bench_array_push(c: &mut Criterion) {
     });
 }
 
+fn bench_array_push_many(c: &mut Criterion) {
+    const N: usize = 100_000;
+    let v: jsonbb::Value = r#"{"d":"qqq"}"#.parse().unwrap();
+    c.bench_function("array_push 100k/jsonbb-ArrayBuilder", |b| {
+        b.iter(|| {
+            let mut builder = jsonbb::ArrayBuilder::new();
+            for _ in 0..N {
+                builder.push(v.as_ref());
+            }
+            builder.finish()
+        })
+    });
+
+    let v: serde_json::Value = r#"{"d":"qqq"}"#.parse().unwrap();
+    c.bench_function("array_push 100k/serde_json", |b| {
+        b.iter(|| {
+            let mut array = vec![];
+            for _ in 0..N {
+                array.push(v.clone());
+            }
+            serde_json::Value::Array(array)
+        })
+    });
+}
+
+fn bench_add_value(c: &mut Criterion) {
+    // a large nested object
+    let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    builder.begin_object();
+    for i in 0..1000 {
+        builder.add_string(&format!("key{i}"));
+        builder.begin_object();
+        builder.add_string("id");
+        builder.add_u64(i);
+        builder.add_string("tags");
+        builder.begin_array();
+        builder.add_string("a");
+        builder.add_f64(i as f64 / 3.0);
+        builder.end_array();
+        builder.end_object();
+    }
+    builder.end_object();
+    let value = builder.finish();
+    let fields: Vec<_> = value.as_object().unwrap().iter().collect();
+
+    c.bench_function("add_value/nested", |b| {
+        b.iter(|| {
+            let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+            builder.add_value(value.as_ref());
+            builder.finish()
+        })
+    });
+    c.bench_function("splice_value/nested", |b| {
+        b.iter(|| {
+            let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+            builder.splice_value(value.as_ref());
+            builder.finish()
+        })
+    });
+    c.bench_function("add_value/leaves", |b| {
+        b.iter(|| {
+            let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+            builder.begin_array();
+            for (_, v) in &fields {
+                builder.add_value(v.get("id").unwrap());
+            }
+            builder.end_array();
+            builder.finish()
+        })
+    });
+    c.bench_function("splice_value/leaves", |b| {
+        b.iter(|| {
+            let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+            builder.begin_array();
+            for (_, v) in &fields {
+                builder.splice_value(v.get("id").unwrap());
+            }
+            builder.end_array();
+            builder.finish()
+        })
+    });
+}
+
 /// Iterate over all files in the `./benches/data/` directory.
 fn iter_json_files() -> impl Iterator<Item = (String, String)> {
     std::fs::read_dir("./benches/data/").unwrap().map(|path| {
@@ -510,6 +593,8 @@ criterion_group!(
     bench_index_array,
     bench_file_index,
     bench_path,
-    bench_array_push
+    bench_array_push,
+    bench_array_push_many,
+    bench_add_value
 );
 criterion_main!(benches);
@@ -0,0 +1,112 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `arbitrary::Arbitrary` implementation for `Value`.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Builder, Value};
+
+/// The maximum nesting depth of generated values.
+const MAX_DEPTH: usize = 4;
+/// The maximum number of elements in a generated array or object.
+const MAX_WIDTH: usize = 8;
+
+/// Numbers at the boundaries of each encoding.
+const EDGE_NUMBERS: &[f64] = &[
+    0.0,
+    -0.0,
+    0.5,
+    -1.5,
+    f64::MIN_POSITIVE,
+    5e-324,
+    f64::MAX,
+    f64::MIN,
+    9007199254740993.0,
+];
+
+/// Generates a document of bounded depth and width, including edge-case numbers and unicode
+/// strings. The value is built with a single [`Builder`].
+impl<'a> Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut builder = Builder::<Vec<u8>>::new();
+        add_arbitrary(&mut builder, u, MAX_DEPTH)?;
+        Ok(builder.finish())
+    }
+}
+
+/// Adds an arbitrary value with at most `depth` levels of nesting.
+fn add_arbitrary(b: &mut Builder, u: &mut Unstructured<'_>, depth: usize) -> Result<()> {
+    let max_kind = if depth == 0 { 3 } else { 5 };
+    match u.int_in_range(0..=max_kind)? {
+        0 => b.add_null(),
+        1 => b.add_bool(u.arbitrary()?),
+        2 => add_arbitrary_number(b, u)?,
+        3 => b.add_string(u.arbitrary()?),
+        4 => {
+            b.begin_array();
+            for _ in 0..u.int_in_range(0..=MAX_WIDTH)? {
+                add_arbitrary(b, u, depth - 1)?;
+            }
+            b.end_array();
+        }
+        _ => {
+            b.begin_object();
+            for _ in 0..u.int_in_range(0..=MAX_WIDTH)? {
+                b.add_string(u.arbitrary()?);
+                add_arbitrary(b, u, depth - 1)?;
+            }
+            b.end_object();
+        }
+    }
+    Ok(())
+}
+
+/// Adds an arbitrary number, covering every encoding.
+fn add_arbitrary_number(b: &mut Builder, u: &mut Unstructured<'_>) -> Result<()> {
+    match u.int_in_range(0..=4)? {
+        0 => b.add_i64(u.arbitrary()?),
+        1 => b.add_u64(u.arbitrary()?),
+        2 => b.add_i64(
+            u.choose(&[0, -1, i64::MIN, i64::MAX, i8::MIN.into(), i32::MAX.into()])?
+                .to_owned(),
+        ),
+        3 => b.add_f64(*u.choose(EDGE_NUMBERS)?),
+        _ => {
+            let f: f64 = u.arbitrary()?;
+            b.add_f64(if f.is_finite() { f } else { 0.0 });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arbitrary_value() {
+        let data: Vec<u8> = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut u = Unstructured::new(&data);
+        while !u.is_empty() {
+            let value = Value::arbitrary(&mut u).unwrap();
+            let text = value.to_string();
+            assert_eq!(Value::from_text(text.as_bytes()).unwrap(), value);
+        }
+        // no data left
+        assert_eq!(Value::arbitrary(&mut u).unwrap(), Value::null());
+    }
+}
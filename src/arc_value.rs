@@ -0,0 +1,390 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::{ArrayRef, Entry, Error, Index, JsonType, ObjectRef, Value, ValueRef};
+
+/// An immutable JSON value in a shared buffer.
+///
+/// This is like [`Value`], but the buffer is reference counted, so cloning is cheap and the
+/// value can be shared across threads without copying the document.
+///
+/// # Example
+///
+/// ```
+/// let value: jsonbb::Value = r#"{"user": {"name": "alice"}}"#.parse().unwrap();
+/// let shared = jsonbb::ArcValue::from(value);
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let shared = shared.clone();
+///         std::thread::spawn(move || shared.pointer("/user/name").unwrap().to_string())
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), r#""alice""#);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ArcValue {
+    buffer: Arc<[u8]>,
+}
+
+impl ArcValue {
+    /// Creates an `ArcValue` from bytes of jsonbb encoding.
+    ///
+    /// The bytes are not checked. See [`Value::from_bytes`] for details.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            buffer: bytes.into(),
+        }
+    }
+
+    /// Creates an `ArcValue` from bytes of jsonbb encoding, checking that they are valid.
+    ///
+    /// See [`ValueRef::try_from_bytes`] for details.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        ValueRef::try_from_bytes(bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// Returns a reference to the value.
+    pub fn as_ref(&self) -> ValueRef<'_> {
+        ValueRef::from_bytes(&self.buffer)
+    }
+
+    /// Returns the value as bytes.
+    ///
+    /// See [`Value::as_bytes`] for details.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Copies the value into an owned [`Value`].
+    pub fn to_value(&self) -> Value {
+        Value::from_bytes(&self.buffer)
+    }
+
+    /// Returns true if the two values share the same buffer.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.buffer, &other.buffer)
+    }
+}
+
+impl From<Value> for ArcValue {
+    fn from(value: Value) -> Self {
+        Self {
+            buffer: value.buffer.into(),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for ArcValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        Value::from(value).into()
+    }
+}
+
+/// An immutable JSON value inside a shared buffer.
+///
+/// This is a part of a larger document, such as an element of an array, that keeps the whole
+/// buffer alive. Cloning is cheap and the value can be sent to other threads without copying.
+///
+/// It is created by [`Value::into_array_iter`].
+#[derive(Clone)]
+pub struct SharedValueSlice {
+    buffer: Arc<[u8]>,
+    /// The range of the value's bytes in the buffer.
+    range: Range<usize>,
+    entry: Entry,
+}
+
+impl SharedValueSlice {
+    /// Creates a slice of `value`, which must be inside `buffer`.
+    fn new(buffer: Arc<[u8]>, value: ValueRef<'_>) -> Self {
+        let slice = value.as_slice();
+        // null and booleans have no bytes, and their slice may not point into the buffer
+        let start = if slice.is_empty() {
+            0
+        } else {
+            slice.as_ptr() as usize - buffer.as_ptr() as usize
+        };
+        Self {
+            range: start..start + slice.len(),
+            entry: value.entry(),
+            buffer,
+        }
+    }
+
+    /// Returns a reference to the value.
+    pub fn as_ref(&self) -> ValueRef<'_> {
+        ValueRef::from_slice(&self.buffer[self.range.clone()], self.entry)
+    }
+
+    /// Copies the value into an owned [`Value`].
+    pub fn to_value(&self) -> Value {
+        self.as_ref().to_owned()
+    }
+}
+
+/// Implements the read-only accessors and traits of a shared value by delegating to `as_ref`.
+macro_rules! impl_shared_value {
+    ($($ty:ty),*) => {$(
+        impl $ty {
+            /// If the value is a boolean, returns the associated bool. Returns `None` otherwise.
+            pub fn as_bool(&self) -> Option<bool> {
+                self.as_ref().as_bool()
+            }
+
+            /// If the value is an integer, returns the associated i64. Returns `None` otherwise.
+            pub fn as_i64(&self) -> Option<i64> {
+                self.as_ref().as_i64()
+            }
+
+            /// If the value is an integer, returns the associated u64. Returns `None` otherwise.
+            pub fn as_u64(&self) -> Option<u64> {
+                self.as_ref().as_u64()
+            }
+
+            /// If the value is a number, returns the associated f64. Returns `None` otherwise.
+            pub fn as_f64(&self) -> Option<f64> {
+                self.as_ref().as_f64()
+            }
+
+            /// If the value is a string, returns the associated str. Returns `None` otherwise.
+            pub fn as_str(&self) -> Option<&str> {
+                self.as_ref().as_str()
+            }
+
+            /// If the value is an array, returns the associated array. Returns `None` otherwise.
+            pub fn as_array(&self) -> Option<ArrayRef<'_>> {
+                self.as_ref().as_array()
+            }
+
+            /// If the value is an object, returns the associated object. Returns `None` otherwise.
+            pub fn as_object(&self) -> Option<ObjectRef<'_>> {
+                self.as_ref().as_object()
+            }
+
+            /// Returns true if the value is a null. Returns false otherwise.
+            pub fn is_null(&self) -> bool {
+                self.as_ref().is_null()
+            }
+
+            /// Returns the type of the value.
+            pub fn json_type(&self) -> JsonType {
+                self.as_ref().json_type()
+            }
+
+            /// Index into a JSON array or object.
+            ///
+            /// See [`Value::get`] for details.
+            pub fn get(&self, index: impl Index) -> Option<ValueRef<'_>> {
+                index.index_into(self.as_ref())
+            }
+
+            /// Looks up a value by a JSON Pointer.
+            ///
+            /// See [`Value::pointer`] for details.
+            pub fn pointer<'a>(&'a self, pointer: &str) -> Option<ValueRef<'a>> {
+                self.as_ref().pointer(pointer)
+            }
+        }
+
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                value.to_value()
+            }
+        }
+
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.as_ref().fmt(f)
+            }
+        }
+
+        /// Display a JSON value as a string.
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.as_ref().fmt(f)
+            }
+        }
+
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                self.as_ref().eq(&other.as_ref())
+            }
+        }
+
+        impl Eq for $ty {}
+
+        impl PartialOrd for $ty {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $ty {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.as_ref().cmp(&other.as_ref())
+            }
+        }
+
+        impl Hash for $ty {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.as_ref().hash(state)
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.as_ref().serialize(serializer)
+            }
+        }
+    )*};
+}
+
+impl_shared_value!(ArcValue, SharedValueSlice);
+
+impl Value {
+    /// Consumes the value and returns an iterator over the elements of the array, or `None`
+    /// if the value is not an array.
+    ///
+    /// The buffer is copied once into a shared allocation, and every element refers to it
+    /// instead of copying its own bytes. The allocation is freed when all elements are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#.parse().unwrap();
+    /// let handles: Vec<_> = value
+    ///     .into_array_iter()
+    ///     .unwrap()
+    ///     .map(|task| std::thread::spawn(move || task.get("id").unwrap().as_u64().unwrap()))
+    ///     .collect();
+    /// let ids: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    /// assert_eq!(ids, [1, 2, 3]);
+    /// ```
+    pub fn into_array_iter(self) -> Option<impl ExactSizeIterator<Item = SharedValueSlice>> {
+        let len = self.as_array()?.len();
+        let buffer: Arc<[u8]> = self.buffer.into();
+        Some((0..len).map(move |i| {
+            let array = ValueRef::from_bytes(&buffer).as_array().unwrap();
+            SharedValueSlice::new(buffer.clone(), array.get(i).unwrap())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query() {
+        let value: Value = r#"{"a": [1, "x", null], "b": {"c": 2.5}}"#.parse().unwrap();
+        let shared = ArcValue::from(value.clone());
+        assert_eq!(shared.as_ref(), value.as_ref());
+        assert_eq!(shared.as_bytes(), value.as_bytes());
+        assert_eq!(shared.to_string(), value.to_string());
+        assert_eq!(shared.get("a").unwrap().get(1).unwrap().as_str(), Some("x"));
+        assert_eq!(shared.pointer("/b/c").unwrap().as_f64(), Some(2.5));
+        assert_eq!(shared.json_type(), JsonType::Object);
+        assert_eq!(shared.as_object().unwrap().len(), 2);
+        assert!(shared.as_array().is_none());
+        assert_eq!(Value::from(shared.clone()), value);
+        assert_eq!(ArcValue::from_bytes(value.as_bytes()), shared);
+        assert_eq!(
+            ArcValue::from(value.get("b").unwrap()).to_string(),
+            r#"{"c":2.5}"#
+        );
+    }
+
+    #[test]
+    fn clone_shares_buffer() {
+        let shared = ArcValue::from(Value::from("x"));
+        let cloned = shared.clone();
+        assert!(ArcValue::ptr_eq(&shared, &cloned));
+        assert!(!ArcValue::ptr_eq(
+            &shared,
+            &ArcValue::from(Value::from("x"))
+        ));
+    }
+
+    #[test]
+    fn share_across_threads() {
+        let json = format!(
+            "[{}]",
+            (0..100)
+                .map(|i| format!(r#"{{"id": {i}, "name": "user{i}"}}"#))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let shared = ArcValue::from(json.parse::<Value>().unwrap());
+        std::thread::scope(|s| {
+            for t in 0..8 {
+                let shared = shared.clone();
+                s.spawn(move || {
+                    for i in (t..100).step_by(8) {
+                        let user = shared.get(i).unwrap();
+                        assert_eq!(user.get("id").unwrap().as_u64(), Some(i as u64));
+                        assert_eq!(
+                            user.get("name").unwrap().as_str(),
+                            Some(format!("user{i}").as_str())
+                        );
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn into_array_iter() {
+        let json = r#"[null, true, false, 0, -1, 1.5, 18446744073709551615, "", "str", [], [1, [2]], {}, {"a": {"b": null}}]"#;
+        let value: Value = json.parse().unwrap();
+        let expected: Vec<Value> = value.as_array().unwrap().iter().map(Value::from).collect();
+
+        let elements: Vec<_> = value.into_array_iter().unwrap().collect();
+        // the original value has been dropped
+        assert_eq!(elements.len(), expected.len());
+        for (element, expected) in elements.iter().zip(&expected) {
+            assert_eq!(element.as_ref(), expected.as_ref());
+            assert_eq!(element.to_string(), expected.to_string());
+            assert_eq!(&element.to_value(), expected);
+        }
+        assert_eq!(elements[12].pointer("/a/b"), Some(ValueRef::Null));
+        assert_eq!(elements[10].get(1).unwrap().to_string(), "[2]");
+
+        // elements share the buffer
+        let buffer = &elements[0].buffer;
+        assert!(elements.iter().all(|e| Arc::ptr_eq(&e.buffer, buffer)));
+        assert_eq!(Arc::strong_count(buffer), elements.len());
+
+        // the buffer outlives the other elements
+        let last = elements.last().unwrap().clone();
+        drop(elements);
+        assert_eq!(last.to_string(), r#"{"a":{"b":null}}"#);
+        assert_eq!(Arc::strong_count(&last.buffer), 1);
+    }
+
+    #[test]
+    fn into_array_iter_not_array() {
+        assert!(Value::from("x").into_array_iter().is_none());
+        let value: Value = "[]".parse().unwrap();
+        assert_eq!(value.into_array_iter().unwrap().len(), 0);
+    }
+}
@@ -25,8 +25,12 @@ pub struct Builder<W = Vec<u8>> {
     ///
     /// Smallvec is used to avoid heap allocation for single value.
     pointers: SmallVec<[Entry; 1]>,
-    /// A stack of (position, number of pointers) pairs when the array/object starts.
-    container_starts: Vec<(usize, usize)>,
+    /// A stack of (position, number of pointers, is object) when the array/object starts.
+    container_starts: Vec<(usize, usize, bool)>,
+    /// How to handle duplicate keys in objects when deserializing.
+    duplicate_keys: DuplicateKeyPolicy,
+    /// Whether to serialize non-finite floats as null instead of panicking.
+    non_finite_as_null: bool,
 }
 
 impl<W> Debug for Builder<W> {
@@ -47,6 +51,8 @@ impl<W: Clone> Clone for Builder<W> {
             buffer: self.buffer.clone(),
             pointers: self.pointers.clone(),
             container_starts: self.container_starts.clone(),
+            duplicate_keys: self.duplicate_keys,
+            non_finite_as_null: self.non_finite_as_null,
         }
     }
 }
@@ -63,6 +69,8 @@ impl Builder<Vec<u8>> {
             buffer: Vec::with_capacity(capacity),
             pointers: SmallVec::new(),
             container_starts: vec![],
+            duplicate_keys: DuplicateKeyPolicy::LastWins,
+            non_finite_as_null: false,
         }
     }
 }
@@ -74,6 +82,8 @@ impl<'a> Builder<&'a mut Vec<u8>> {
             buffer,
             pointers: SmallVec::new(),
             container_starts: vec![],
+            duplicate_keys: DuplicateKeyPolicy::LastWins,
+            non_finite_as_null: false,
         }
     }
 }
@@ -125,16 +135,31 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     }
 
     /// Adds an f64 value to the builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is NaN or infinite. Use [`try_add_f64`] to handle this case.
+    ///
+    /// [`try_add_f64`]: #method.try_add_f64
     pub fn add_f64(&mut self, v: f64) {
-        assert!(
-            !v.is_nan() && !v.is_infinite(),
-            "Infinite or NaN values are not JSON numbers"
-        );
+        if let Err(e) = self.try_add_f64(v) {
+            panic!("{e}");
+        }
+    }
+
+    /// Adds an f64 value to the builder.
+    ///
+    /// Returns an error without modifying the builder if the value is NaN or infinite.
+    pub fn try_add_f64(&mut self, v: f64) -> Result<(), NonFiniteError> {
+        if !v.is_finite() {
+            return Err(NonFiniteError(v));
+        }
         let offset = self.offset();
         self.pointers.push(Entry::number(offset));
         let buffer = self.buffer.as_mut();
         buffer.push(NUMBER_F64);
         buffer.put_f64_ne(v);
+        Ok(())
     }
 
     /// Adds a string value to the builder.
@@ -163,6 +188,16 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         (&mut buffer[offset..]).put_u32_ne(len.try_into().expect("string too long"));
     }
 
+    /// Adds a base64-encoded string of the given bytes to the builder.
+    ///
+    /// Use [`ValueRef::as_base64_bytes`] to decode it.
+    #[cfg(feature = "base64")]
+    pub fn add_bytes_base64(&mut self, bytes: &[u8]) {
+        use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+
+        self.display(Base64Display::new(bytes, &STANDARD));
+    }
+
     /// Begins an array.
     ///
     /// The caller then needs to push the elements and call [`end_array`] to finish the array.
@@ -171,13 +206,13 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     pub fn begin_array(&mut self) {
         let buffer = self.buffer.as_mut();
         self.container_starts
-            .push((buffer.len(), self.pointers.len()));
+            .push((buffer.len(), self.pointers.len(), false));
     }
 
     /// Ends an array.
     pub fn end_array(&mut self) {
         let buffer = self.buffer.as_mut();
-        let (start, npointer) = self.container_starts.pop().unwrap();
+        let (start, npointer, _) = self.container_starts.pop().unwrap();
         let len = self.pointers.len() - npointer;
         buffer.reserve(4 * len + 4 + 4);
         for entry in self.pointers.drain(npointer..) {
@@ -206,7 +241,7 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     pub fn begin_object(&mut self) {
         let buffer = self.buffer.as_mut();
         self.container_starts
-            .push((buffer.len(), self.pointers.len()));
+            .push((buffer.len(), self.pointers.len(), true));
     }
 
     /// Ends an object.
@@ -219,10 +254,40 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     ///
     /// [`begin_object`]: #method.begin_object
     pub fn end_object(&mut self) {
+        self.end_object_with(DuplicateKeyPolicy::LastWins)
+            .expect("last-wins never fails");
+    }
+
+    /// Sets how to handle duplicate keys in objects when deserializing.
+    pub(crate) fn set_duplicate_keys(&mut self, policy: DuplicateKeyPolicy) {
+        self.duplicate_keys = policy;
+    }
+
+    /// Sets whether serializing a non-finite float produces null instead of panicking.
+    pub(crate) fn set_non_finite_as_null(&mut self, enabled: bool) {
+        self.non_finite_as_null = enabled;
+    }
+
+    /// Returns whether serializing a non-finite float produces null.
+    pub(crate) fn non_finite_as_null(&self) -> bool {
+        self.non_finite_as_null
+    }
+
+    /// Ends an object during deserialization, handling duplicate keys with the configured policy.
+    ///
+    /// Returns [`Error::Build`] on a duplicate key if the policy is [`DuplicateKeyPolicy::Error`].
+    pub(crate) fn end_object_checked(&mut self) -> Result<(), Error> {
+        self.end_object_with(self.duplicate_keys)
+    }
+
+    /// Ends an object, handling duplicate keys with the given policy.
+    ///
+    /// On error, the object is left unfinished and its members are only reordered.
+    fn end_object_with(&mut self, policy: DuplicateKeyPolicy) -> Result<(), Error> {
         let buffer = self.buffer.as_mut();
-        let (start, npointer) = self.container_starts.pop().unwrap();
+        let (start, npointer, _) = *self.container_starts.last().unwrap();
         assert!(
-            (self.pointers.len() - npointer) % 2 == 0,
+            (self.pointers.len() - npointer).is_multiple_of(2),
             "expected even number of entries"
         );
         let len = (self.pointers.len() - npointer) / 2;
@@ -252,6 +317,18 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         };
         entries.sort_by_key(|(k, _)| entry_to_str(*k));
 
+        // reject duplicate keys before modifying anything else
+        if policy == DuplicateKeyPolicy::Error {
+            if let Some(w) = entries
+                .windows(2)
+                .find(|w| entry_to_str(w[0].0) == entry_to_str(w[1].0))
+            {
+                let key = entry_to_str(w[0].0);
+                return Err(Error::Build(format!("duplicate key `{key}` in object")));
+            }
+        }
+        self.container_starts.pop();
+
         // deduplicate keys
         let mut prev_key = None;
         let mut unique_len = 0;
@@ -262,7 +339,11 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
                 entries[unique_len] = entries[i];
                 unique_len += 1;
             } else {
-                entries[unique_len - 1] = entries[i];
+                match policy {
+                    DuplicateKeyPolicy::LastWins => entries[unique_len - 1] = entries[i],
+                    DuplicateKeyPolicy::FirstWins => {}
+                    DuplicateKeyPolicy::Error => unreachable!("checked above"),
+                }
             }
         }
 
@@ -317,9 +398,16 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         let offset = self.offset();
         self.pointers.truncate(npointer);
         self.pointers.push(Entry::object(offset));
+        Ok(())
     }
 
     /// Adds a JSON value to the builder.
+    ///
+    /// Arrays and objects are copied with a single `memcpy` of their encoded bytes, so the cost
+    /// is linear in the encoded size and no node is visited. Scalars are re-encoded. See
+    /// [`splice_value`] to copy scalars as raw bytes as well.
+    ///
+    /// [`splice_value`]: #method.splice_value
     pub fn add_value(&mut self, value: ValueRef<'_>) {
         match value {
             ValueRef::Null => self.add_null(),
@@ -351,6 +439,64 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         }
     }
 
+    /// Adds a JSON value to the builder by copying its encoded bytes.
+    ///
+    /// Any value, scalar or container, is copied with a single `memcpy`. This works because
+    /// offsets inside a subtree are relative to the subtree itself, so no fix-up is needed.
+    /// The result is identical to [`add_value`].
+    ///
+    /// [`add_value`]: #method.add_value
+    pub fn splice_value(&mut self, value: ValueRef<'_>) {
+        let offset = self.offset();
+        self.buffer.as_mut().extend_from_slice(value.as_slice());
+        self.pointers.push(value.make_entry(offset));
+    }
+
+    /// Parses successive JSON values from a reader and adds each of them to the builder.
+    ///
+    /// This is intended to be called between [`begin_array`] and [`end_array`], so that a stream
+    /// of whitespace-separated values (e.g. NDJSON) is ingested into one array without building
+    /// an intermediate `Value` for each document.
+    ///
+    /// Returns the number of values added. If an error occurs, the values parsed before it are
+    /// kept and the partially parsed value is discarded.
+    ///
+    /// [`begin_array`]: #method.begin_array
+    /// [`end_array`]: #method.end_array
+    ///
+    /// # Example
+    /// ```
+    /// let ndjson = b"{\"id\": 1}\n{\"id\": 2}\n\n[3]\n";
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// builder.begin_array();
+    /// let n = builder.extend_from_reader(&ndjson[..]).unwrap();
+    /// builder.end_array();
+    /// assert_eq!(n, 3);
+    /// assert_eq!(builder.finish().to_string(), r#"[{"id":1},{"id":2},[3]]"#);
+    /// ```
+    pub fn extend_from_reader<R: std::io::Read>(&mut self, reader: R) -> Result<usize, Error> {
+        use ::serde::de::DeserializeSeed;
+
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let mut count = 0;
+        // `end` succeeds only if nothing but whitespace remains.
+        while deserializer.end().is_err() {
+            let len = self.buffer.as_mut().len();
+            let npointer = self.pointers.len();
+            let ncontainer = self.container_starts.len();
+            if let Err(e) = self.deserialize(&mut deserializer) {
+                let e = self.check_size().err().unwrap_or(e.into());
+                // roll back the partially parsed value
+                self.buffer.as_mut().truncate(len);
+                self.pointers.truncate(npointer);
+                self.container_starts.truncate(ncontainer);
+                return Err(e);
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
     /// Finishes building.
     fn finish_internal(mut self) -> W {
         assert_eq!(self.pointers.len(), 1, "expected single root value");
@@ -361,9 +507,24 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         self.buffer
     }
 
+    /// Returns an error if the offsets in the document may exceed what an entry can address.
+    ///
+    /// The size is estimated conservatively, including the entries that are yet to be written
+    /// when the open arrays and objects are ended.
+    pub(crate) fn check_size(&mut self) -> Result<(), Error> {
+        let size = self.buffer.as_mut().len()
+            + 4 * self.pointers.len()
+            + 8 * self.container_starts.len()
+            + 4;
+        if size > Entry::MAX_OFFSET {
+            return Err(Error::DocumentTooLarge { size });
+        }
+        Ok(())
+    }
+
     /// Get the current offset from the array/object start.
     fn offset(&mut self) -> usize {
-        self.buffer.as_mut().len() - self.container_starts.last().map_or(0, |&(o, _)| o)
+        self.buffer.as_mut().len() - self.container_starts.last().map_or(0, |&(o, ..)| o)
     }
 
     /// Pops the last value.
@@ -374,13 +535,80 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
             return;
         }
         let buffer = self.buffer.as_mut();
-        let new_len = entry.offset() + self.container_starts.last().map_or(0, |&(o, _)| o);
+        let new_len = entry.offset() + self.container_starts.last().map_or(0, |&(o, ..)| o);
         buffer.truncate(new_len);
         if entry.is_array() || entry.is_object() {
             let len = (&buffer[new_len - 4..]).get_u32_ne() as usize;
             buffer.truncate(new_len - len);
         }
     }
+
+    /// Returns the number of arrays and objects that have begun but not ended.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// assert_eq!(builder.current_depth(), 0);
+    /// builder.begin_array();
+    /// builder.begin_object();
+    /// assert_eq!(builder.current_depth(), 2);
+    /// builder.end_object();
+    /// assert_eq!(builder.current_depth(), 1);
+    /// ```
+    pub fn current_depth(&self) -> usize {
+        self.container_starts.len()
+    }
+
+    /// Returns the number of values added to the innermost unfinished array or object.
+    ///
+    /// In an object, keys and values are counted separately. Outside of any container, this is
+    /// the number of top-level values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// builder.begin_object();
+    /// builder.add_string("a");
+    /// assert_eq!(builder.current_len(), 1);
+    /// builder.begin_array();
+    /// assert_eq!(builder.current_len(), 0);
+    /// builder.add_u64(1);
+    /// builder.end_array();
+    /// assert_eq!(builder.current_len(), 2);
+    /// ```
+    pub fn current_len(&self) -> usize {
+        let start = self
+            .container_starts
+            .last()
+            .map_or(0, |&(_, npointer, _)| npointer);
+        self.pointers.len() - start
+    }
+
+    /// Returns `true` if the innermost unfinished container is an object.
+    ///
+    /// Together with [`current_len`], this tells whether a key or a value is expected next:
+    /// a key is expected if the length is even.
+    ///
+    /// [`current_len`]: #method.current_len
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// assert!(!builder.in_object());
+    /// builder.begin_object();
+    /// assert!(builder.in_object());
+    /// builder.add_string("a");
+    /// builder.begin_array();
+    /// assert!(!builder.in_object());
+    /// ```
+    pub fn in_object(&self) -> bool {
+        self.container_starts
+            .last()
+            .is_some_and(|&(_, _, is_object)| is_object)
+    }
 }
 
 impl Builder<Vec<u8>> {
@@ -397,16 +625,130 @@ impl Builder<Vec<u8>> {
     }
 }
 
-impl<'a> Builder<&'a mut Vec<u8>> {
+impl Builder<&mut Vec<u8>> {
     /// Finishes building.
     pub fn finish(self) {
         self.finish_internal();
     }
 }
 
+/// A builder for JSON arrays with amortized `O(1)` appends.
+///
+/// Unlike [`Value::array_push`], which rebuilds the array footer on every call, this builder
+/// defers writing the footer until [`finish`] is called.
+///
+/// [`finish`]: #method.finish
+///
+/// # Example
+///
+/// ```
+/// let mut builder = jsonbb::ArrayBuilder::new();
+/// for i in 0..3 {
+///     builder.push(jsonbb::Value::from(i).as_ref());
+/// }
+/// assert_eq!(builder.finish().to_string(), "[0,1,2]");
+/// ```
+///
+/// It also implements [`Extend`] for iterator-driven construction:
+///
+/// ```
+/// let strings = ["a", "b"];
+/// let mut builder = jsonbb::ArrayBuilder::new();
+/// builder.extend(strings.iter().map(|s| jsonbb::ValueRef::String(s)));
+/// assert_eq!(builder.finish().to_string(), r#"["a","b"]"#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrayBuilder {
+    builder: Builder,
+    len: usize,
+}
+
+impl Default for ArrayBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArrayBuilder {
+    /// Creates a new [`ArrayBuilder`].
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates a new [`ArrayBuilder`] with capacity in bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut builder = Builder::with_capacity(capacity);
+        builder.begin_array();
+        ArrayBuilder { builder, len: 0 }
+    }
+
+    /// Appends a value to the end of the array.
+    pub fn push(&mut self, value: ValueRef<'_>) {
+        self.builder.add_value(value);
+        self.len += 1;
+    }
+
+    /// Returns the number of elements pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no element has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Finishes building and returns the array.
+    pub fn finish(mut self) -> Value {
+        self.builder.end_array();
+        self.builder.finish()
+    }
+}
+
+impl<'a> Extend<ValueRef<'a>> for ArrayBuilder {
+    fn extend<T: IntoIterator<Item = ValueRef<'a>>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// How to handle duplicate keys in an object when parsing JSON text.
+///
+/// See [`ParseOptions::duplicate_keys`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the value of the last occurrence. This is the default.
+    #[default]
+    LastWins,
+    /// Keep the value of the first occurrence.
+    FirstWins,
+    /// Fail with an error.
+    Error,
+}
+
+/// An error returned when adding a NaN or infinite float, which is not a valid JSON number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonFiniteError(f64);
+
+impl NonFiniteError {
+    /// Returns the rejected value.
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Infinite or NaN values are not JSON numbers: {}", self.0)
+    }
+}
+
+impl std::error::Error for NonFiniteError {}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Builder, Value};
+    use crate::{ArrayBuilder, Builder, DuplicateKeyPolicy, Error, Value, ValueRef};
 
     #[test]
     fn unique_key() {
@@ -420,6 +762,154 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "base64")]
+    fn base64() {
+        for bytes in [&b""[..], b"\x00\xff", b"hello world"] {
+            let mut builder = Builder::<Vec<u8>>::new();
+            builder.add_bytes_base64(bytes);
+            let value = builder.finish();
+            assert_eq!(value.as_ref().as_base64_bytes().unwrap(), bytes);
+        }
+
+        let value = Value::from("aGVsbG8=");
+        assert_eq!(value.as_ref().as_base64_bytes().unwrap(), b"hello");
+        assert_eq!(Value::from("not base64!").as_ref().as_base64_bytes(), None);
+        assert_eq!(Value::from(1).as_ref().as_base64_bytes(), None);
+    }
+
+    #[test]
+    fn array_builder() {
+        let mut builder = ArrayBuilder::new();
+        assert!(builder.is_empty());
+        let mut expected = vec![];
+        for i in 0..10_000u64 {
+            let v = match i % 4 {
+                0 => Value::from(i),
+                1 => Value::from(i.to_string().as_str()),
+                2 => Value::array([Value::from(i).as_ref()]),
+                _ => Value::null(),
+            };
+            builder.push(v.as_ref());
+            expected.push(v);
+        }
+        assert_eq!(builder.len(), 10_000);
+        let value = builder.finish();
+        assert_eq!(value, Value::array(expected.iter().map(|v| v.as_ref())));
+        let array = value.as_array().unwrap();
+        assert_eq!(
+            array.get(9998).unwrap().get(0).unwrap().as_u64(),
+            Some(9998)
+        );
+        assert_eq!(array.get(9997).unwrap().as_str(), Some("9997"));
+
+        assert_eq!(ArrayBuilder::new().finish().to_string(), "[]");
+    }
+
+    #[test]
+    fn extend_from_reader() {
+        let ndjson = "1\n\"two\"\n[3]\n{\"four\": 4}\nnull\n";
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        builder.add_bool(true);
+        let n = builder.extend_from_reader(ndjson.as_bytes()).unwrap();
+        assert_eq!(n, 5);
+        builder.end_array();
+        let value = builder.finish();
+        assert_eq!(value.to_string(), r#"[true,1,"two",[3],{"four":4},null]"#);
+
+        // values before an error are kept
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        let err = builder
+            .extend_from_reader("[1]\n{\"a\": [2, \n".as_bytes())
+            .unwrap_err();
+        assert!(matches!(err, Error::Parse(e) if e.is_eof()));
+        builder.end_array();
+        assert_eq!(builder.finish().to_string(), "[[1]]");
+
+        // empty input
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        assert_eq!(builder.extend_from_reader(" \n ".as_bytes()).unwrap(), 0);
+        builder.end_array();
+        assert_eq!(builder.finish().to_string(), "[]");
+    }
+
+    #[test]
+    fn current_depth_and_in_object() {
+        let mut builder = Builder::<Vec<u8>>::new();
+        assert_eq!((builder.current_depth(), builder.in_object()), (0, false));
+        builder.begin_object();
+        assert_eq!((builder.current_depth(), builder.in_object()), (1, true));
+        builder.add_string("a");
+        builder.begin_array();
+        assert_eq!((builder.current_depth(), builder.in_object()), (2, false));
+        builder.begin_object();
+        assert_eq!((builder.current_depth(), builder.in_object()), (3, true));
+        builder.end_object();
+        assert_eq!((builder.current_depth(), builder.in_object()), (2, false));
+        builder.end_array();
+        assert_eq!((builder.current_depth(), builder.in_object()), (1, true));
+        builder.end_object();
+        assert_eq!((builder.current_depth(), builder.in_object()), (0, false));
+        assert_eq!(builder.finish().to_string(), r#"{"a":[{}]}"#);
+    }
+
+    #[test]
+    fn array_builder_extend() {
+        let values: Vec<Value> = (0..100u64).map(Value::from).collect();
+        let mut builder = ArrayBuilder::new();
+        builder.extend(values.iter().map(|v| v.as_ref()));
+        builder.extend([ValueRef::Null, ValueRef::String("end")]);
+        assert_eq!(builder.len(), 102);
+
+        let expected = Value::array(
+            values
+                .iter()
+                .map(|v| v.as_ref())
+                .chain([ValueRef::Null, ValueRef::String("end")]),
+        );
+        assert_eq!(builder.finish(), expected);
+    }
+
+    #[test]
+    fn splice_value() {
+        let value: Value = r#"{
+            "a": [1, -1, 300, -70000, 18446744073709551615, 1.5, 0],
+            "b": {"c": "string", "d": [null, true, false, {}, []]},
+            "e": ""
+        }"#
+        .parse()
+        .unwrap();
+        let mut values = vec![value.as_ref()];
+        values.extend(value.as_object().unwrap().values());
+        values.extend(value.get("a").unwrap().as_array().unwrap().iter());
+        values.extend(value.pointer("/b/d").unwrap().as_array().unwrap().iter());
+
+        let mut added = Builder::<Vec<u8>>::new();
+        let mut spliced = Builder::<Vec<u8>>::new();
+        added.begin_object();
+        spliced.begin_object();
+        for (i, v) in values.iter().enumerate() {
+            added.add_string(&i.to_string());
+            added.add_value(*v);
+            spliced.add_string(&i.to_string());
+            spliced.splice_value(*v);
+
+            // also at the top level
+            let mut b = Builder::<Vec<u8>>::new();
+            b.splice_value(*v);
+            assert_eq!(b.finish().as_ref(), *v);
+        }
+        added.end_object();
+        spliced.end_object();
+        let added = added.finish();
+        let spliced = spliced.finish();
+        assert_eq!(added.as_bytes(), spliced.as_bytes());
+        assert_eq!(added, spliced);
+    }
+
     #[test]
     fn pop() {
         let mut builder = Builder::<Vec<u8>>::new();
@@ -438,4 +928,26 @@ mod tests {
         let value = builder.finish();
         assert_eq!(value.to_string(), "[1,4]");
     }
+
+    #[test]
+    fn duplicate_key_error_keeps_object_open() {
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.set_duplicate_keys(DuplicateKeyPolicy::Error);
+        builder.begin_object();
+        builder.add_string("b");
+        builder.add_u64(1);
+        builder.add_string("a");
+        builder.add_u64(2);
+        builder.add_string("b");
+        builder.add_u64(3);
+        let err = builder.end_object_checked().unwrap_err();
+        assert_eq!(err.to_string(), "duplicate key `b` in object");
+        assert_eq!(builder.current_depth(), 1);
+        assert!(builder.in_object());
+        assert_eq!(builder.current_len(), 6);
+
+        builder.set_duplicate_keys(DuplicateKeyPolicy::LastWins);
+        builder.end_object_checked().unwrap();
+        assert_eq!(builder.finish().to_string(), r#"{"a":2,"b":3}"#);
+    }
 }
@@ -12,42 +12,64 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+/// The 4-byte entry of a JSON node, consisting of a 3-bit type tag and a 29-bit offset.
+///
+/// The offset points to the payload of the node. It is relative to the start of the slice that
+/// contains the node: the payload of the enclosing array or object, or the whole buffer of a
+/// top-level value. See the [crate-level docs] for the encoding format.
+///
+/// [crate-level docs]: crate#encoding-format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
-pub struct Entry(pub [u8; 4]);
+pub struct Entry(pub(crate) [u8; 4]);
 
 impl Entry {
     const LEN_MASK: u32 = 0x1FFFFFFF;
 
+    /// The maximum offset that an entry can address.
+    pub(crate) const MAX_OFFSET: usize = Self::LEN_MASK as usize;
+
+    /// The tag of `null`.
     pub const NULL_TAG: u32 = 0;
+    /// The tag of a string.
     pub const STRING_TAG: u32 = 1;
+    /// The tag of a number.
     pub const NUMBER_TAG: u32 = 2;
+    /// The tag of `false`.
     pub const FALSE_TAG: u32 = 3;
+    /// The tag of `true`.
     pub const TRUE_TAG: u32 = 4;
+    /// The tag of an array.
     pub const ARRAY_TAG: u32 = 5;
+    /// The tag of an object.
     pub const OBJECT_TAG: u32 = 6;
 
+    /// Returns the type tag, one of the `*_TAG` constants.
     pub const fn tag(self) -> u32 {
         u32::from_ne_bytes(self.0) >> 29
     }
 
+    /// Returns the offset of the payload, relative to the start of the containing slice.
+    ///
+    /// For arrays and objects this points to the end of the payload. It is 0 for `null` and
+    /// booleans, which have no payload.
     pub const fn offset(self) -> usize {
         (u32::from_ne_bytes(self.0) & Self::LEN_MASK) as usize
     }
 
-    pub const fn null() -> Self {
+    pub(crate) const fn null() -> Self {
         Self::from_u32(Self::NULL_TAG << 29)
     }
 
-    pub const fn false_() -> Self {
+    pub(crate) const fn false_() -> Self {
         Self::from_u32(Self::FALSE_TAG << 29)
     }
 
-    pub const fn true_() -> Self {
+    pub(crate) const fn true_() -> Self {
         Self::from_u32(Self::TRUE_TAG << 29)
     }
 
-    pub const fn bool(b: bool) -> Self {
+    pub(crate) const fn bool(b: bool) -> Self {
         if b {
             Self::true_()
         } else {
@@ -55,47 +77,52 @@ impl Entry {
         }
     }
 
-    pub const fn number(offset: usize) -> Self {
+    pub(crate) const fn number(offset: usize) -> Self {
         assert!(offset <= Self::LEN_MASK as usize, "offset too large");
         Self::from_u32((Self::NUMBER_TAG << 29) | (offset as u32))
     }
 
-    pub const fn string(offset: usize) -> Self {
+    pub(crate) const fn string(offset: usize) -> Self {
         assert!(offset <= Self::LEN_MASK as usize, "offset too large");
         Self::from_u32((Self::STRING_TAG << 29) | (offset as u32))
     }
 
-    pub const fn array(offset: usize) -> Self {
+    pub(crate) const fn array(offset: usize) -> Self {
         assert!(offset <= Self::LEN_MASK as usize, "offset too large");
         Self::from_u32((Self::ARRAY_TAG << 29) | (offset as u32))
     }
 
-    pub const fn object(offset: usize) -> Self {
+    pub(crate) const fn object(offset: usize) -> Self {
         assert!(offset <= Self::LEN_MASK as usize, "offset too large");
         Self::from_u32((Self::OBJECT_TAG << 29) | (offset as u32))
     }
 
+    /// Returns true if the entry is a number.
     pub const fn is_number(self) -> bool {
         self.tag() == Self::NUMBER_TAG
     }
 
+    /// Returns true if the entry is a string.
     pub const fn is_string(self) -> bool {
         self.tag() == Self::STRING_TAG
     }
 
+    /// Returns true if the entry is an array.
     pub const fn is_array(self) -> bool {
         self.tag() == Self::ARRAY_TAG
     }
 
+    /// Returns true if the entry is an object.
     pub const fn is_object(self) -> bool {
         self.tag() == Self::OBJECT_TAG
     }
 
-    pub fn set_offset(&mut self, offset: usize) {
+    pub(crate) fn set_offset(&mut self, offset: usize) {
         assert!(offset <= Self::LEN_MASK as usize, "offset too large");
         self.0 = ((self.tag() << 29) | (offset as u32)).to_ne_bytes();
     }
 
+    /// Returns the raw bytes of the entry, in native byte order.
     pub const fn as_bytes(&self) -> &[u8] {
         &self.0
     }
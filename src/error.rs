@@ -0,0 +1,239 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use crate::{ElementTypeError, JsonType, NonFiniteError, OutOfRangeError, TryFromValueError};
+
+/// The error type of fallible operations in this crate.
+///
+/// Operations that can fail in only one way return a dedicated error type, such as
+/// [`NonFiniteError`] or [`TryFromValueError`]. All of them convert into this type, so they can
+/// be propagated together with `?`.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::{Error, JsonType, Value};
+///
+/// fn parse_u64(json: &str) -> Result<u64, Error> {
+///     let value: Value = json.parse()?;
+///     Ok(value.as_ref().try_into()?)
+/// }
+///
+/// assert_eq!(parse_u64("3").unwrap(), 3);
+/// assert!(matches!(parse_u64("3."), Err(Error::Parse(_))));
+/// assert!(matches!(
+///     parse_u64(r#""3""#),
+///     Err(Error::InvalidType { expected: JsonType::Number, found: JsonType::String })
+/// ));
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input is not valid JSON text.
+    Parse(serde_json::Error),
+    /// The input is not valid JSON text, as reported by `simd-json`.
+    #[cfg(feature = "simd-json")]
+    Simd(simd_json::Error),
+    /// A float is NaN or infinite, which is not a valid JSON number.
+    NonFinite(NonFiniteError),
+    /// An integer is out of the range of JSON numbers.
+    IntegerOutOfRange(OutOfRangeError),
+    /// The value is not of the expected type.
+    InvalidType {
+        /// The expected type.
+        expected: JsonType,
+        /// The actual type of the value.
+        found: JsonType,
+    },
+    /// A number can not be represented by the target type without loss.
+    InvalidNumber {
+        /// The name of the target type.
+        target: &'static str,
+    },
+    /// An array element does not have the expected type.
+    InvalidElement(ElementTypeError),
+    /// An index or range is out of the bounds of an array, e.g. in
+    /// [`Value::try_splice_array`].
+    ///
+    /// For a range whose start is greater than its end, `index` is the start and `len` is the
+    /// end.
+    ///
+    /// [`Value::try_splice_array`]: crate::Value::try_splice_array
+    OutOfBounds {
+        /// The index.
+        index: usize,
+        /// The length of the array.
+        len: usize,
+    },
+    /// Bytes are not a valid jsonbb encoding.
+    InvalidEncoding {
+        /// What is wrong with the bytes.
+        reason: &'static str,
+    },
+    /// A value can not be built, e.g. a serialized map key is not a string or an object has a
+    /// duplicate key that is rejected by [`DuplicateKeyPolicy::Error`].
+    ///
+    /// [`DuplicateKeyPolicy::Error`]: crate::DuplicateKeyPolicy::Error
+    Build(String),
+    /// The encoded document is larger than the format can address.
+    DocumentTooLarge {
+        /// The size of the document in bytes.
+        size: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => e.fmt(f),
+            #[cfg(feature = "simd-json")]
+            Self::Simd(e) => e.fmt(f),
+            Self::NonFinite(e) => e.fmt(f),
+            Self::IntegerOutOfRange(e) => e.fmt(f),
+            Self::InvalidType { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            Self::InvalidNumber { target } => {
+                write!(f, "number can not be represented as {target}")
+            }
+            Self::InvalidElement(e) => e.fmt(f),
+            Self::OutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "index {index} is out of bounds for array of length {len}"
+                )
+            }
+            Self::InvalidEncoding { reason } => {
+                write!(f, "invalid jsonbb encoding: {reason}")
+            }
+            Self::Build(msg) => f.write_str(msg),
+            Self::DocumentTooLarge { size } => {
+                write!(f, "document of {size} bytes is too large")
+            }
+        }
+    }
+}
+
+/// Wrapped errors are displayed transparently, so they are not reported again as the source.
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Build(msg.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+#[cfg(feature = "simd-json")]
+impl From<simd_json::Error> for Error {
+    fn from(e: simd_json::Error) -> Self {
+        Self::Simd(e)
+    }
+}
+
+impl From<NonFiniteError> for Error {
+    fn from(e: NonFiniteError) -> Self {
+        Self::NonFinite(e)
+    }
+}
+
+impl From<OutOfRangeError> for Error {
+    fn from(e: OutOfRangeError) -> Self {
+        Self::IntegerOutOfRange(e)
+    }
+}
+
+impl From<ElementTypeError> for Error {
+    fn from(e: ElementTypeError) -> Self {
+        Self::InvalidElement(e)
+    }
+}
+
+impl From<TryFromValueError> for Error {
+    fn from(e: TryFromValueError) -> Self {
+        match e {
+            TryFromValueError::TypeMismatch { expected, found } => {
+                Self::InvalidType { expected, found }
+            }
+            TryFromValueError::InvalidNumber { target } => Self::InvalidNumber { target },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    #[test]
+    fn from_errors() {
+        let err = Value::from_text(b"[1,").unwrap_err();
+        assert!(matches!(err, Error::Parse(_)));
+
+        let err = Error::from(Value::try_from_f64(f64::NAN).unwrap_err());
+        assert!(matches!(err, Error::NonFinite(_)));
+        assert_eq!(
+            err.to_string(),
+            "Infinite or NaN values are not JSON numbers: NaN"
+        );
+
+        let err = Error::from(Value::try_from(u128::MAX).unwrap_err());
+        assert!(matches!(err, Error::IntegerOutOfRange(_)));
+
+        let value = Value::from("x");
+        let err = Error::from(bool::try_from(value.as_ref()).unwrap_err());
+        assert!(matches!(
+            err,
+            Error::InvalidType {
+                expected: JsonType::Boolean,
+                found: JsonType::String
+            }
+        ));
+        assert_eq!(err.to_string(), "expected boolean, found string");
+
+        let err = Error::from(u8::try_from(Value::from(300).as_ref()).unwrap_err());
+        assert_eq!(err.to_string(), "number can not be represented as u8");
+
+        let err = Error::OutOfBounds { index: 3, len: 2 };
+        assert_eq!(
+            err.to_string(),
+            "index 3 is out of bounds for array of length 2"
+        );
+    }
+
+    #[cfg(feature = "simd-json")]
+    #[test]
+    fn simd() {
+        let mut json = b"[1,".to_vec();
+        let err = Value::from_text_mut(&mut json).unwrap_err();
+        assert!(matches!(err, Error::Simd(_)));
+    }
+
+    #[test]
+    fn source() {
+        use std::error::Error as _;
+
+        // wrapped errors are displayed transparently and not repeated as the source
+        let err = Value::from_text(b"[1,").unwrap_err();
+        assert!(err.source().is_none());
+        assert!(Error::OutOfBounds { index: 0, len: 0 }.source().is_none());
+    }
+}
@@ -67,11 +67,11 @@
 //! It has the following key features:
 //!
 //! 1. Memory Continuity: The content of any JSON subtree is stored contiguously, allowing for
-//! efficient copying through `memcpy`. This leads to highly efficient indexing operations.
+//!    efficient copying through `memcpy`. This leads to highly efficient indexing operations.
 //!
 //! 2. Post-Order Traversal: JSON nodes are stored in post-order traversal sequence. When parsing
-//! JSON strings, output can be sequentially written to the buffer without additional memory
-//! allocation and movement. This results in highly efficient parsing operations.
+//!    JSON strings, output can be sequentially written to the buffer without additional memory
+//!    allocation and movement. This results in highly efficient parsing operations.
 //!
 //! Each JSON node consists of a fixed-size **entry** and a variable-length **payload**.
 //! Each entry is 4 bytes, with 3 bits storing the node type and 29 bits storing the offset of
@@ -110,20 +110,58 @@
 //! where:   len = ptr - start
 //! ```
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod arc_value;
 mod builder;
 mod entry;
+mod error;
+#[cfg(feature = "schemars")]
+mod json_schema;
 mod macros;
 mod partial_eq;
+#[cfg(feature = "sqlx-postgres")]
+mod postgres;
+mod pretty;
+#[cfg(feature = "pyo3")]
+mod python;
 mod serde;
+mod shape;
+#[cfg(any(test, feature = "proptest"))]
+pub mod testing;
+mod try_from;
 mod value;
 mod value_ref;
+#[cfg(feature = "wasm-bindgen")]
+mod wasm;
 
+pub use self::arc_value::{ArcValue, SharedValueSlice};
 pub use self::builder::*;
+pub use self::entry::Entry;
 use self::entry::*;
+pub use self::error::Error;
+pub use self::pretty::{minify, prettify};
+#[cfg(feature = "pyo3")]
+pub use self::python::FromPyObjectOptions;
 pub use self::serde::*;
+pub use self::shape::*;
+pub use self::try_from::*;
 pub use self::value::*;
 pub use self::value_ref::*;
 
 // for `json!` macro
 #[doc(hidden)]
 pub use serde_json;
+#[doc(hidden)]
+pub mod __private {
+    pub use serde::Serialize;
+
+    /// Creates the builder used by the `json!` macro.
+    ///
+    /// Interpolated NaN and infinity become null, consistent with `serde_json::json!`.
+    pub fn json_builder() -> crate::Builder {
+        let mut builder = crate::Builder::<Vec<u8>>::new();
+        builder.set_non_finite_as_null(true);
+        builder
+    }
+}
@@ -15,9 +15,252 @@
 ///     }
 /// });
 /// ```
+///
+/// Any expression that implements `serde::Serialize` can be interpolated. Object keys can be any
+/// expression that implements `AsRef<str>`.
+///
+/// ```
+/// # use jsonbb::json;
+/// #
+/// let code = 200;
+/// let features = vec!["serde", "json"];
+/// let key = String::from("code");
+/// let value = json!({ key: code, "features": features });
+/// assert_eq!(value.to_string(), r#"{"code":200,"features":["serde","json"]}"#);
+/// ```
+///
+/// Non-finite floats are interpolated as `null`, as in `serde_json::json!`.
+///
+/// Iterables can be spread into arrays and objects with `..`. In arrays, the iterator yields
+/// values. In objects, it yields `(key, value)` pairs. Duplicate keys in an object are resolved
+/// in favor of the last one.
+///
+/// ```
+/// # use jsonbb::json;
+/// # use std::collections::BTreeMap;
+/// #
+/// let more = [3, 4];
+/// assert_eq!(json!([1, 2, ..more]).to_string(), "[1,2,3,4]");
+///
+/// let extra = BTreeMap::from([("a", 2), ("b", 3)]);
+/// assert_eq!(json!({"a": 1, ..&extra}).to_string(), r#"{"a":2,"b":3}"#);
+/// ```
+///
+/// Prefixing a value with `?` makes the field optional. The value must be an `Option`, and
+/// the field is omitted when it is `None`.
+///
+/// ```
+/// # use jsonbb::json;
+/// #
+/// let (some, none) = (Some(1), None::<i32>);
+/// assert_eq!(json!({"a": ?some, "b": ?none}).to_string(), r#"{"a":1}"#);
+/// ```
+///
+/// The value is built in a single [`Builder`](crate::Builder) without constructing
+/// intermediate values for nested elements.
+///
+/// Misuse is rejected at compile time:
+///
+/// ```compile_fail
+/// # use jsonbb::json;
+/// // missing value
+/// json!({"a": });
+/// ```
+///
+/// ```compile_fail
+/// # use jsonbb::json;
+/// // missing colon
+/// json!({"a" 1});
+/// ```
+///
+/// ```compile_fail
+/// # use jsonbb::json;
+/// // optional values are only allowed in objects
+/// json!([?Some(1)]);
+/// ```
+///
+/// ```compile_fail
+/// # use jsonbb::json;
+/// // object spread must yield key-value pairs
+/// json!({..[1, 2]});
+/// ```
 #[macro_export(local_inner_macros)]
 macro_rules! json {
-    ($($json:tt)+) => {
-        jsonbb::Value::from(jsonbb::serde_json::json!($($json)+))
+    ($($json:tt)+) => {{
+        let mut builder = $crate::__private::json_builder();
+        json_internal!(@value builder $($json)+);
+        builder.finish()
+    }};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    //////////////////////////////////////////////////////////////////////////
+    // Adds a single value to the builder.
+    //
+    // Invocation: json_internal!(@value $builder $($tt)+)
+    //////////////////////////////////////////////////////////////////////////
+
+    (@value $b:ident null) => {
+        $b.add_null();
+    };
+
+    (@value $b:ident true) => {
+        $b.add_bool(true);
+    };
+
+    (@value $b:ident false) => {
+        $b.add_bool(false);
+    };
+
+    (@value $b:ident [$($array:tt)*]) => {
+        $b.begin_array();
+        $crate::json_internal!(@array $b $($array)*);
+        $b.end_array();
+    };
+
+    (@value $b:ident {$($object:tt)*}) => {
+        $b.begin_object();
+        $crate::json_internal!(@object $b () ($($object)*));
+        $b.end_object();
+    };
+
+    (@value $b:ident $other:expr) => {
+        $crate::__private::Serialize::serialize(&$other, &mut $b)
+            .expect("failed to serialize value");
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // Adds the elements of an array.
+    //
+    // Invocation: json_internal!(@array $builder $($tt)*)
+    //////////////////////////////////////////////////////////////////////////
+
+    // Done.
+    (@array $b:ident) => {};
+
+    // Spread an iterable.
+    (@array $b:ident .. $iter:expr $(, $($rest:tt)*)?) => {
+        for value in $iter {
+            $crate::json_internal!(@value $b value);
+        }
+        $crate::json_internal!(@array $b $($($rest)*)?);
+    };
+
+    // Literals, nested arrays and nested objects.
+    (@array $b:ident null $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@value $b null);
+        $crate::json_internal!(@array $b $($($rest)*)?);
+    };
+
+    (@array $b:ident true $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@value $b true);
+        $crate::json_internal!(@array $b $($($rest)*)?);
+    };
+
+    (@array $b:ident false $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@value $b false);
+        $crate::json_internal!(@array $b $($($rest)*)?);
+    };
+
+    (@array $b:ident [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@value $b [$($array)*]);
+        $crate::json_internal!(@array $b $($($rest)*)?);
     };
+
+    (@array $b:ident {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@value $b {$($object)*});
+        $crate::json_internal!(@array $b $($($rest)*)?);
+    };
+
+    // Any other expression.
+    (@array $b:ident $next:expr $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@value $b $next);
+        $crate::json_internal!(@array $b $($($rest)*)?);
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // Adds the members of an object.
+    //
+    // Invocation: json_internal!(@object $builder ($($key)*) ($($tt)*))
+    //
+    // The key is munched token by token until a colon is found.
+    //////////////////////////////////////////////////////////////////////////
+
+    // Done.
+    (@object $b:ident () ()) => {};
+
+    // Spread an iterable of key-value pairs.
+    (@object $b:ident () (.. $iter:expr $(, $($rest:tt)*)?)) => {
+        for (key, value) in $iter {
+            $b.add_string(::core::convert::AsRef::<str>::as_ref(&key));
+            $crate::json_internal!(@value $b value);
+        }
+        $crate::json_internal!(@object $b () ($($($rest)*)?));
+    };
+
+    // Optional value, omitted if `None`.
+    (@object $b:ident ($($key:tt)+) (: ? $value:expr $(, $($rest:tt)*)?)) => {
+        if let ::core::option::Option::Some(value) = $value {
+            $b.add_string(::core::convert::AsRef::<str>::as_ref(&($($key)+)));
+            $crate::json_internal!(@value $b value);
+        }
+        $crate::json_internal!(@object $b () ($($($rest)*)?));
+    };
+
+    // Literals, nested arrays and nested objects.
+    (@object $b:ident ($($key:tt)+) (: null $(, $($rest:tt)*)?)) => {
+        $crate::json_internal!(@member $b ($($key)+) (null) ($($($rest)*)?));
+    };
+
+    (@object $b:ident ($($key:tt)+) (: true $(, $($rest:tt)*)?)) => {
+        $crate::json_internal!(@member $b ($($key)+) (true) ($($($rest)*)?));
+    };
+
+    (@object $b:ident ($($key:tt)+) (: false $(, $($rest:tt)*)?)) => {
+        $crate::json_internal!(@member $b ($($key)+) (false) ($($($rest)*)?));
+    };
+
+    (@object $b:ident ($($key:tt)+) (: [$($array:tt)*] $(, $($rest:tt)*)?)) => {
+        $crate::json_internal!(@member $b ($($key)+) ([$($array)*]) ($($($rest)*)?));
+    };
+
+    (@object $b:ident ($($key:tt)+) (: {$($object:tt)*} $(, $($rest:tt)*)?)) => {
+        $crate::json_internal!(@member $b ($($key)+) ({$($object)*}) ($($($rest)*)?));
+    };
+
+    // Any other expression.
+    (@object $b:ident ($($key:tt)+) (: $value:expr $(, $($rest:tt)*)?)) => {
+        $crate::json_internal!(@member $b ($($key)+) ($value) ($($($rest)*)?));
+    };
+
+    // Missing colon or value, report an unexpected end of macro invocation.
+    (@object $b:ident ($($key:tt)+) ()) => {
+        $crate::json_internal!();
+    };
+
+    // A colon without a key.
+    (@object $b:ident () (: $($rest:tt)*)) => {
+        $crate::json_unexpected!(:);
+    };
+
+    // Munch a token into the current key.
+    (@object $b:ident ($($key:tt)*) ($tt:tt $($rest:tt)*)) => {
+        $crate::json_internal!(@object $b ($($key)* $tt) ($($rest)*));
+    };
+
+    // Adds a key-value pair and continues with the rest of the object.
+    (@member $b:ident ($($key:tt)+) ($($value:tt)+) ($($rest:tt)*)) => {
+        $b.add_string(::core::convert::AsRef::<str>::as_ref(&($($key)+)));
+        $crate::json_internal!(@value $b $($value)+);
+        $crate::json_internal!(@object $b () ($($rest)*));
+    };
+}
+
+/// Used to produce an error pointing at an unexpected token.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_unexpected {
+    () => {};
 }
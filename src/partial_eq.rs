@@ -14,35 +14,36 @@
 
 //! `PartialEq` implementations for `ValueRef` and `Value`.
 
+use crate::value_ref::number_eq;
 use crate::ValueRef;
 
 use super::Value;
 
 fn eq_i64(value: ValueRef<'_>, other: i64) -> bool {
-    value.as_i64().map_or(false, |i| i == other)
+    value.as_i64().is_some_and(|i| i == other)
 }
 
 fn eq_u64(value: ValueRef<'_>, other: u64) -> bool {
-    value.as_u64().map_or(false, |i| i == other)
+    value.as_u64().is_some_and(|i| i == other)
 }
 
 fn eq_f32(value: ValueRef<'_>, other: f32) -> bool {
     match value {
-        ValueRef::Number(n) => n.as_f32().map_or(false, |i| i == other),
+        ValueRef::Number(n) => n.as_f32().is_some_and(|i| i == other),
         _ => false,
     }
 }
 
 fn eq_f64(value: ValueRef<'_>, other: f64) -> bool {
-    value.as_f64().map_or(false, |i| i == other)
+    value.as_f64().is_some_and(|i| i == other)
 }
 
 fn eq_bool(value: ValueRef<'_>, other: bool) -> bool {
-    value.as_bool().map_or(false, |i| i == other)
+    value.as_bool().is_some_and(|i| i == other)
 }
 
 fn eq_str(value: ValueRef<'_>, other: &str) -> bool {
-    value.as_str().map_or(false, |i| i == other)
+    value.as_str().is_some_and(|i| i == other)
 }
 
 impl PartialEq<str> for ValueRef<'_> {
@@ -81,6 +82,60 @@ impl PartialEq<ValueRef<'_>> for String {
     }
 }
 
+fn eq_serde(value: ValueRef<'_>, other: &serde_json::Value) -> bool {
+    use serde_json::Value as V;
+    match (value, other) {
+        (ValueRef::Null, V::Null) => true,
+        (ValueRef::Bool(a), V::Bool(b)) => a == *b,
+        (ValueRef::Number(a), V::Number(b)) => number_eq(&a.to_number(), b),
+        (ValueRef::String(a), V::String(b)) => a == b,
+        (ValueRef::Array(a), V::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| eq_serde(a, b))
+        }
+        (ValueRef::Object(a), V::Object(b)) => {
+            a.len() == b.len()
+                && b.iter()
+                    .all(|(k, v)| a.get(k).is_some_and(|a| eq_serde(a, v)))
+        }
+        _ => false,
+    }
+}
+
+/// Compares with a `serde_json::Value` using the same semantics as comparing two `ValueRef`s.
+///
+/// # Example
+///
+/// ```
+/// let a: jsonbb::Value = r#"{"a": [1, "x"]}"#.parse().unwrap();
+/// let b: serde_json::Value = r#"{"a": [1.0, "x"]}"#.parse().unwrap();
+/// assert_eq!(a.as_ref(), b);
+/// assert_eq!(a, b);
+/// assert_eq!(b, a);
+/// ```
+impl PartialEq<serde_json::Value> for ValueRef<'_> {
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        eq_serde(*self, other)
+    }
+}
+
+impl PartialEq<ValueRef<'_>> for serde_json::Value {
+    fn eq(&self, other: &ValueRef<'_>) -> bool {
+        eq_serde(*other, self)
+    }
+}
+
+impl PartialEq<serde_json::Value> for Value {
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        eq_serde(self.as_ref(), other)
+    }
+}
+
+impl PartialEq<Value> for serde_json::Value {
+    fn eq(&self, other: &Value) -> bool {
+        eq_serde(other.as_ref(), self)
+    }
+}
+
 macro_rules! partialeq_numeric {
     ($($eq:ident [$($ty:ty)*])*) => {
         $($(
@@ -96,7 +151,7 @@ macro_rules! partialeq_numeric {
                 }
             }
 
-            impl<'a> PartialEq<$ty> for &'a Value {
+            impl PartialEq<$ty> for &Value {
                 fn eq(&self, other: &$ty) -> bool {
                     $eq(self.as_ref(), *other as _)
                 }
@@ -112,3 +167,41 @@ partialeq_numeric! {
     eq_f64[f64]
     eq_bool[bool]
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn eq_serde_json() {
+        #[track_caller]
+        fn check(a: &str, b: &str, expected: bool) {
+            let a: Value = a.parse().unwrap();
+            let b: serde_json::Value = b.parse().unwrap();
+            assert_eq!(a == b, expected);
+            assert_eq!(b == a, expected);
+            assert_eq!(a.as_ref() == b, expected);
+        }
+        check("null", "null", true);
+        check("true", "true", true);
+        check("1", "1", true);
+        check("1", "1.0", true);
+        check("-1", "-1.0", true);
+        check("18446744073709551615", "18446744073709551615", true);
+        check(r#""a""#, r#""a""#, true);
+        check(r#"[1, [2]]"#, r#"[1, [2.0]]"#, true);
+        check(
+            r#"{"a": 1, "b": {"c": null}}"#,
+            r#"{"b": {"c": null}, "a": 1}"#,
+            true,
+        );
+
+        check("null", "false", false);
+        check("1", "2", false);
+        check("1", r#""1""#, false);
+        check("1", "-1", false);
+        check("[1, 2]", "[1]", false);
+        check(r#"{"a": 1}"#, r#"{"b": 1}"#, false);
+        check(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#, false);
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `sqlx` support for storing `Value` in Postgres `JSONB` and `JSON` columns.
+//!
+//! Values are encoded in the `JSONB` wire format, which is a version byte followed by JSON text,
+//! and decoded by parsing the payload directly into a `Builder`.
+
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{
+    PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueFormat, PgValueRef, Postgres,
+};
+use sqlx::types::Json;
+use sqlx::{Decode, Encode, Type, ValueRef as _};
+
+use crate::Value;
+
+/// The version of the `JSONB` binary format.
+const JSONB_VERSION: u8 = 1;
+
+impl Type<Postgres> for Value {
+    fn type_info() -> PgTypeInfo {
+        <Json<()> as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Json<()> as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for Value {
+    fn array_type_info() -> PgTypeInfo {
+        <Json<()> as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <Json<()> as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for Value {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        // `Json` writes the version byte and replaces it with a space if the parameter
+        // turns out to be `JSON` rather than `JSONB`.
+        Json(self).encode_by_ref(buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Value {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let is_jsonb = value.format() == PgValueFormat::Binary
+            && *value.type_info() == <Self as Type<Postgres>>::type_info();
+        decode(value.as_bytes()?, is_jsonb)
+    }
+}
+
+/// Decodes a value from the payload of a `JSON` or `JSONB` column.
+///
+/// Binary `JSONB` payloads are prefixed with a version byte. All other payloads are plain JSON text.
+fn decode(payload: &[u8], is_jsonb: bool) -> Result<Value, BoxDynError> {
+    let text = match payload {
+        [JSONB_VERSION, text @ ..] if is_jsonb => text,
+        [version, ..] if is_jsonb => {
+            return Err(format!("unsupported JSONB format version {version}").into())
+        }
+        [] if is_jsonb => return Err("empty JSONB payload".into()),
+        text => text,
+    };
+    Ok(Value::from_text(text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        let value: Value = r#"{"a": [1, "x", null]}"#.parse().unwrap();
+        let mut buf = PgArgumentBuffer::default();
+        let is_null = Encode::<Postgres>::encode_by_ref(&value, &mut buf).unwrap();
+        assert!(matches!(is_null, IsNull::No));
+        assert_eq!(&buf[..], b"\x01{\"a\":[1,\"x\",null]}");
+    }
+
+    #[test]
+    fn decode_jsonb() {
+        let value = decode(b"\x01{\"b\": 2, \"a\": [1.5, true]}", true).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":[1.5,true],"b":2}"#);
+
+        let err = decode(b"\x02{}", true).unwrap_err();
+        assert_eq!(err.to_string(), "unsupported JSONB format version 2");
+        let err = decode(b"", true).unwrap_err();
+        assert_eq!(err.to_string(), "empty JSONB payload");
+        assert!(decode(b"\x01{", true).is_err());
+    }
+
+    #[test]
+    fn decode_text() {
+        // text format and `JSON` columns carry plain JSON text
+        let value = decode(r#" {"a": "é"} "#.as_bytes(), false).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":"é"}"#);
+        assert!(decode(b"\x01{}", false).is_err());
+    }
+
+    #[test]
+    fn type_info() {
+        use sqlx::TypeInfo;
+
+        assert_eq!(<Value as Type<Postgres>>::type_info().name(), "JSONB");
+        assert_eq!(
+            <Value as PgHasArrayType>::array_type_info().name(),
+            "JSONB[]"
+        );
+    }
+
+    /// Round-trips values through a real Postgres server.
+    ///
+    /// Skipped unless `DATABASE_URL` is set. Requires an async runtime feature of `sqlx`, e.g.
+    /// `cargo test --features sqlx-postgres,sqlx/runtime-tokio`.
+    #[test]
+    fn postgres_roundtrip() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return;
+        };
+        sqlx::test_block_on(async move {
+            use sqlx::Connection;
+
+            let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+            let value: Value = r#"{"b": [1, 2.5, "x"], "a": null}"#.parse().unwrap();
+
+            let (jsonb, json): (Value, Value) = sqlx::query_as("SELECT $1::jsonb, $1::json")
+                .bind(&value)
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+            assert_eq!(jsonb, value);
+            assert_eq!(json, value);
+
+            let text: String = sqlx::query_scalar("SELECT $1::jsonb::text")
+                .bind(&value)
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+            assert_eq!(text, r#"{"a": null, "b": [1, 2.5, "x"]}"#);
+
+            let array: Vec<Value> = sqlx::query_scalar("SELECT ARRAY['1'::jsonb, '[true]']")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+            assert_eq!(array, [Value::from(1), "[true]".parse().unwrap()]);
+        });
+    }
+}
@@ -0,0 +1,664 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Width-aware pretty printing, truncated previews and reformatting of JSON text.
+
+use std::fmt::{self, Write};
+
+use crate::{Error, Value, ValueRef};
+
+impl ValueRef<'_> {
+    /// Serializes the value as pretty-printed JSON text, keeping arrays and objects on one line
+    /// when they fit within `width` bytes.
+    ///
+    /// A container that doesn't fit is expanded with one element per line, indented by
+    /// `indent` per level. Its children are then laid out with the same rule. Scalars are
+    /// never split, so a long string may exceed the width.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"name": "jsonbb", "tags": ["json", "binary"]}"#.parse().unwrap();
+    /// assert_eq!(
+    ///     value.as_ref().to_string_pretty_width(80, "  "),
+    ///     r#"{"name": "jsonbb", "tags": ["json", "binary"]}"#
+    /// );
+    /// assert_eq!(
+    ///     value.as_ref().to_string_pretty_width(30, "  "),
+    ///     r#"{
+    ///   "name": "jsonbb",
+    ///   "tags": ["json", "binary"]
+    /// }"#
+    /// );
+    /// ```
+    pub fn to_string_pretty_width(self, width: usize, indent: &str) -> String {
+        let mut printer = Printer {
+            out: String::new(),
+            width,
+            indent,
+        };
+        printer.write(self, 0, 0, 0);
+        printer.out
+    }
+}
+
+impl<'a> ValueRef<'a> {
+    /// Returns an object that displays the value as compact JSON text of roughly at most
+    /// `max_len` bytes, for logging.
+    ///
+    /// A value whose JSON text fits is displayed verbatim. Otherwise content is elided once the
+    /// budget is used up:
+    ///
+    /// - Long strings are cut at a character boundary, e.g. `"abcd…"(+12034 chars)`.
+    /// - Long arrays end with the number of remaining elements, e.g. `[1,2,…, +998 items]`.
+    /// - Long objects end with the number of remaining members, e.g. `{"a":1,…, +5 keys}`, and
+    ///   objects with no room left become `{…}`.
+    ///
+    /// Once something is elided, the enclosing arrays and objects are closed without further
+    /// content, so deeply nested values stay within the budget too.
+    ///
+    /// The result is not valid JSON then. The full JSON text is never materialized, so this is
+    /// cheap even for large values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"id": 1, "msg": "hello world", "tags": [1, 2, 3, 4, 5]}"#
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     value.as_ref().display_truncated(100).to_string(),
+    ///     r#"{"id":1,"msg":"hello world","tags":[1,2,3,4,5]}"#
+    /// );
+    /// assert_eq!(
+    ///     value.as_ref().display_truncated(44).to_string(),
+    ///     r#"{"id":1,"msg":"hello world",…, +1 key}"#
+    /// );
+    ///
+    /// let value = jsonbb::Value::from("abcdefghijklmnopqrstuvwxyz".repeat(4));
+    /// assert_eq!(value.as_ref().display_truncated(20).to_string(), r#""abcd…"(+100 chars)"#);
+    /// ```
+    pub fn display_truncated(self, max_len: usize) -> impl fmt::Display + 'a {
+        DisplayTruncated {
+            value: self,
+            max_len,
+        }
+    }
+}
+
+impl Value {
+    /// Serializes the value as pretty-printed JSON text, keeping arrays and objects on one line
+    /// when they fit within `width` bytes.
+    ///
+    /// See [`ValueRef::to_string_pretty_width`] for details.
+    pub fn to_string_pretty_width(&self, width: usize, indent: &str) -> String {
+        self.as_ref().to_string_pretty_width(width, indent)
+    }
+
+    /// Returns an object that displays the value as compact JSON text of roughly at most
+    /// `max_len` bytes, for logging.
+    ///
+    /// See [`ValueRef::display_truncated`] for details.
+    pub fn display_truncated(&self, max_len: usize) -> impl fmt::Display + '_ {
+        self.as_ref().display_truncated(max_len)
+    }
+}
+
+/// Reformats JSON text into its compact form.
+///
+/// The text is parsed into a [`Value`] and serialized again, so object keys are sorted and
+/// duplicate keys are removed, keeping the last value.
+///
+/// # Example
+///
+/// ```
+/// let json = b"{\n  \"b\": [1, 2],\n  \"a\": null\n}";
+/// assert_eq!(jsonbb::minify(json).unwrap(), r#"{"a":null,"b":[1,2]}"#);
+/// ```
+pub fn minify(json: &[u8]) -> Result<String, Error> {
+    Ok(Value::from_text(json)?.to_string())
+}
+
+/// Reformats JSON text with one element per line, indented by `indent` spaces per level.
+///
+/// The text is parsed into a [`Value`] and serialized again, so object keys are sorted and
+/// duplicate keys are removed, keeping the last value.
+///
+/// # Example
+///
+/// ```
+/// let json = br#"{"a":[1,2]}"#;
+/// assert_eq!(jsonbb::prettify(json, 4).unwrap(), "{\n    \"a\": [\n        1,\n        2\n    ]\n}");
+/// ```
+pub fn prettify(json: &[u8], indent: usize) -> Result<String, Error> {
+    use serde::Serialize;
+
+    let value = Value::from_text(json)?;
+    let indent = " ".repeat(indent);
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut buf = Vec::with_capacity(json.len());
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    value.serialize(&mut ser)?;
+    // SAFETY: the serializer only emits valid utf8
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+struct Printer<'a> {
+    out: String,
+    width: usize,
+    indent: &'a str,
+}
+
+impl Printer<'_> {
+    /// Writes a value at the given nesting level.
+    ///
+    /// `prefix_len` is the length of the text already written on the current line after the
+    /// indentation, e.g. the key of an object member. `suffix_len` is the length of the text
+    /// that will follow the value on the same line, e.g. the `,` after an element.
+    fn write(&mut self, value: ValueRef<'_>, level: usize, prefix_len: usize, suffix_len: usize) {
+        let expandable = match value {
+            ValueRef::Array(a) => !a.is_empty(),
+            ValueRef::Object(o) => !o.is_empty(),
+            _ => false,
+        };
+        if !expandable {
+            // scalars and empty containers can't be split
+            write!(self.out, "{value}").unwrap();
+            return;
+        }
+        let start = self.out.len();
+        let used = level * self.indent.len() + prefix_len + suffix_len;
+        let limit = start + self.width.saturating_sub(used);
+        if self.write_inline(value, limit) {
+            return;
+        }
+        self.out.truncate(start);
+        match value {
+            ValueRef::Array(a) => {
+                self.out.push('[');
+                for (i, v) in a.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(level + 1);
+                    let comma_len = usize::from(i + 1 != a.len());
+                    self.write(v, level + 1, 0, comma_len);
+                }
+                self.newline(level);
+                self.out.push(']');
+            }
+            ValueRef::Object(o) => {
+                self.out.push('{');
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(level + 1);
+                    let key_start = self.out.len();
+                    write!(self.out, "{}: ", ValueRef::String(k)).unwrap();
+                    let key_len = self.out.len() - key_start;
+                    let comma_len = usize::from(i + 1 != o.len());
+                    self.write(v, level + 1, key_len, comma_len);
+                }
+                self.newline(level);
+                self.out.push('}');
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Writes a value on a single line.
+    ///
+    /// Returns false as soon as the output exceeds `limit`.
+    fn write_inline(&mut self, value: ValueRef<'_>, limit: usize) -> bool {
+        match value {
+            ValueRef::Array(a) => {
+                self.out.push('[');
+                for (i, v) in a.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push_str(", ");
+                    }
+                    if !self.write_inline(v, limit) {
+                        return false;
+                    }
+                }
+                self.out.push(']');
+            }
+            ValueRef::Object(o) => {
+                self.out.push('{');
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push_str(", ");
+                    }
+                    write!(self.out, "{}: ", ValueRef::String(k)).unwrap();
+                    if !self.write_inline(v, limit) {
+                        return false;
+                    }
+                }
+                self.out.push('}');
+            }
+            _ => write!(self.out, "{value}").unwrap(),
+        }
+        self.out.len() <= limit
+    }
+
+    fn newline(&mut self, level: usize) {
+        self.out.push('\n');
+        for _ in 0..level {
+            self.out.push_str(self.indent);
+        }
+    }
+}
+
+struct DisplayTruncated<'a> {
+    value: ValueRef<'a>,
+    max_len: usize,
+}
+
+impl fmt::Display for DisplayTruncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // serialize into a buffer of at most `max_len` bytes, which fails early if it's too long
+        let mut buf = LimitedWriter {
+            buf: Vec::with_capacity(self.max_len.min(self.value.capacity())),
+            limit: self.max_len,
+        };
+        if serde_json::to_writer(&mut buf, &self.value).is_ok() {
+            // SAFETY: the serializer only emits valid utf8
+            return f.write_str(unsafe { std::str::from_utf8_unchecked(&buf.buf) });
+        }
+        Truncator {
+            f,
+            remaining: self.max_len,
+            depth: 0,
+            exhausted: false,
+        }
+        .write(self.value)
+    }
+}
+
+/// A writer that fails once more than `limit` bytes are written.
+struct LimitedWriter {
+    buf: Vec<u8>,
+    limit: usize,
+}
+
+impl std::io::Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + buf.len() > self.limit {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes a value with content elided once `remaining` bytes are used up.
+///
+/// Before writing anything, enough bytes are kept for a summary such as `…, +998 items]` and for
+/// one closing bracket per open container. Once something is elided, all open containers are
+/// closed right away without further content, so the output stays within roughly `max_len`
+/// bytes however deep the value is nested.
+struct Truncator<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    remaining: usize,
+    /// The number of open arrays and objects.
+    depth: usize,
+    /// Whether content has been elided.
+    exhausted: bool,
+}
+
+impl Truncator<'_, '_> {
+    /// The number of bytes kept in reserve for closing a container or a string with a summary
+    /// such as `…, +998 items]`.
+    const RESERVE: usize = 16;
+
+    /// Returns true if `len` more bytes can be written while keeping the reserve.
+    fn fits(&self, len: usize) -> bool {
+        self.remaining >= len + Self::RESERVE + self.depth
+    }
+
+    fn write(&mut self, value: ValueRef<'_>) -> fmt::Result {
+        match value {
+            ValueRef::String(s) => self.write_string(s),
+            ValueRef::Array(a) if a.is_empty() => self.write_str("[]"),
+            ValueRef::Object(o) if o.is_empty() => self.write_str("{}"),
+            ValueRef::Array(_) | ValueRef::Object(_) if !self.fits(2) => {
+                self.exhausted = true;
+                self.write_str(if value.is_array() { "[…]" } else { "{…}" })
+            }
+            ValueRef::Array(a) => {
+                self.write_str("[")?;
+                self.depth += 1;
+                for (i, v) in a.iter().enumerate() {
+                    if !self.fits(1 + min_len(v)) {
+                        let n = a.len() - i;
+                        let sep = if i == 0 { "" } else { "," };
+                        self.exhausted = true;
+                        self.write_fmt(format_args!("{sep}…, +{n} {}", plural(n, "item")))?;
+                        break;
+                    }
+                    if i != 0 {
+                        self.write_str(",")?;
+                    }
+                    self.write(v)?;
+                    if self.exhausted {
+                        break;
+                    }
+                }
+                self.depth -= 1;
+                self.write_str("]")
+            }
+            ValueRef::Object(o) => {
+                self.write_str("{")?;
+                self.depth += 1;
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if !self.fits(1 + k.len() + 3 + min_len(v)) {
+                        let n = o.len() - i;
+                        let sep = if i == 0 { "" } else { "," };
+                        self.exhausted = true;
+                        self.write_fmt(format_args!("{sep}…, +{n} {}", plural(n, "key")))?;
+                        break;
+                    }
+                    if i != 0 {
+                        self.write_str(",")?;
+                    }
+                    self.write_string(k)?;
+                    if self.exhausted {
+                        break;
+                    }
+                    self.write_str(":")?;
+                    self.write(v)?;
+                    if self.exhausted {
+                        break;
+                    }
+                }
+                self.depth -= 1;
+                self.write_str("}")
+            }
+            _ => {
+                let text = value.to_string();
+                if !self.fits(text.len()) {
+                    self.exhausted = true;
+                    return self.write_str("…");
+                }
+                self.write_str(&text)
+            }
+        }
+    }
+
+    /// Writes a string, cut at a character boundary if it doesn't fit.
+    fn write_string(&mut self, s: &str) -> fmt::Result {
+        let quoted_len = 2 + s
+            .chars()
+            .map(|c| escape(c, &mut [0; 6]).len())
+            .sum::<usize>();
+        if self.fits(quoted_len) || (self.depth == 0 && quoted_len <= self.remaining) {
+            self.write_str("\"")?;
+            for c in s.chars() {
+                self.write_str(escape(c, &mut [0; 6]))?;
+            }
+            return self.write_str("\"");
+        }
+        let mut budget = self.remaining.saturating_sub(Self::RESERVE + self.depth);
+        self.write_str("\"")?;
+        for (i, c) in s.char_indices() {
+            let mut buf = [0; 6];
+            let escaped = escape(c, &mut buf);
+            if escaped.len() > budget {
+                self.exhausted = true;
+                let rest = s[i..].chars().count();
+                return self.write_fmt(format_args!("…\"(+{rest} {})", plural(rest, "char")));
+            }
+            budget -= escaped.len();
+            self.write_str(escaped)?;
+        }
+        // the content fits, only the quotes run into the reserve
+        self.write_str("\"")
+    }
+
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.remaining = self.remaining.saturating_sub(s.len());
+        self.f.write_str(s)
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        match args.as_str() {
+            Some(s) => self.write_str(s),
+            None => self.write_str(&args.to_string()),
+        }
+    }
+}
+
+/// Returns the number of bytes needed to write at least a part of the value.
+fn min_len(value: ValueRef<'_>) -> usize {
+    match value {
+        ValueRef::String(_) | ValueRef::Array(_) | ValueRef::Object(_) => 2,
+        _ => value.to_string().len(),
+    }
+}
+
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        noun.to_string()
+    } else {
+        format!("{noun}s")
+    }
+}
+
+/// Escapes a character in a JSON string the same way as `serde_json`.
+fn escape(c: char, buf: &mut [u8; 6]) -> &str {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    match c {
+        '"' => "\\\"",
+        '\\' => "\\\\",
+        '\u{8}' => "\\b",
+        '\u{c}' => "\\f",
+        '\n' => "\\n",
+        '\r' => "\\r",
+        '\t' => "\\t",
+        '\0'..='\u{1f}' => {
+            *buf = [
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[c as usize >> 4],
+                HEX[c as usize & 0xF],
+            ];
+            std::str::from_utf8(buf).unwrap()
+        }
+        _ => c.encode_utf8(buf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{minify, prettify, Value};
+
+    fn truncated(value: &Value, max_len: usize) -> String {
+        value.display_truncated(max_len).to_string()
+    }
+
+    #[test]
+    fn minify_and_prettify() {
+        let json = b" {\n\t\"b\" : [ 1 , 2.5 ,\r\n {} ] ,  \"a\":\"x y\"  }\n ";
+        assert_eq!(minify(json).unwrap(), r#"{"a":"x y","b":[1,2.5,{}]}"#);
+
+        let compact = br#"{"a":"x y","b":[1,[],{"c":null}]}"#;
+        assert_eq!(
+            prettify(compact, 2).unwrap(),
+            r#"{
+  "a": "x y",
+  "b": [
+    1,
+    [],
+    {
+      "c": null
+    }
+  ]
+}"#
+        );
+        assert_eq!(prettify(b"[1]", 0).unwrap(), "[\n1\n]");
+        assert_eq!(prettify(b"true", 4).unwrap(), "true");
+
+        assert!(minify(b"[1,").is_err());
+        assert!(prettify(b"", 2).is_err());
+    }
+
+    #[test]
+    fn small_stays_inline() {
+        let value: Value = r#"{"a": 1, "b": [true, null], "c": {}}"#.parse().unwrap();
+        assert_eq!(
+            value.to_string_pretty_width(80, "  "),
+            r#"{"a": 1, "b": [true, null], "c": {}}"#
+        );
+        assert_eq!(
+            Value::from("a long string").to_string_pretty_width(4, "  "),
+            r#""a long string""#
+        );
+        assert_eq!(Value::array([]).to_string_pretty_width(0, "  "), "[]");
+    }
+
+    #[test]
+    fn trailing_comma_counts_toward_width() {
+        // `  [1, 2]` is 8 columns wide, but 9 with the comma after it
+        let value: Value = "[[1, 2], 3]".parse().unwrap();
+        assert_eq!(
+            value.to_string_pretty_width(8, "  "),
+            "[\n  [\n    1,\n    2\n  ],\n  3\n]"
+        );
+        // the last element has no comma, so it fits
+        let value: Value = "[3, [1, 2]]".parse().unwrap();
+        assert_eq!(
+            value.to_string_pretty_width(8, "  "),
+            "[\n  3,\n  [1, 2]\n]"
+        );
+    }
+
+    #[test]
+    fn large_expands() {
+        let value: Value = r#"{
+            "id": 12345,
+            "name": "a fairly long name",
+            "tags": ["x", "y"],
+            "matrix": [[1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12], [13, 14, 15]]
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            value.to_string_pretty_width(40, "    "),
+            r#"
+{
+    "id": 12345,
+    "matrix": [
+        [1, 2, 3],
+        [4, 5, 6],
+        [7, 8, 9],
+        [10, 11, 12],
+        [13, 14, 15]
+    ],
+    "name": "a fairly long name",
+    "tags": ["x", "y"]
+}"#
+            .trim_start()
+        );
+        // with a zero width every container is expanded
+        let value: Value = r#"[1, [2]]"#.parse().unwrap();
+        assert_eq!(
+            value.to_string_pretty_width(0, "\t"),
+            "[\n\t1,\n\t[\n\t\t2\n\t]\n]"
+        );
+    }
+
+    #[test]
+    fn display_truncated_fits() {
+        let value: Value = r#"{"a": [1, 2.5, null, true], "b": "x\ny\u0001", "c": {}}"#
+            .parse()
+            .unwrap();
+        let json = value.to_string();
+        assert_eq!(truncated(&value, json.len()), json);
+        assert_eq!(truncated(&value, 1000), json);
+        assert_eq!(truncated(&Value::from(1), 1), "1");
+    }
+
+    #[test]
+    fn display_truncated_string() {
+        let value = Value::from("abcdefghijklmnopqrstuvwxyz".repeat(4));
+        assert_eq!(truncated(&value, 20), r#""abcd…"(+100 chars)"#);
+
+        // never cut inside an escape sequence or a character
+        let value = Value::from("\n\n\n\n\"\"\"\"éééé\u{1}\u{1}\u{1}\u{1}");
+        for max_len in 0..value.to_string().len() {
+            let s = truncated(&value, max_len);
+            let (prefix, rest) = s.split_once('…').unwrap();
+            assert!(!prefix.ends_with('\\') && !prefix.ends_with("\\u00"), "{s}");
+            let kept = Value::from_text(format!("{prefix}\"").as_bytes()).unwrap();
+            let kept = kept.as_str().unwrap().chars().count();
+            let n = 16 - kept;
+            assert_eq!(
+                rest,
+                format!("\"(+{n} {})", if n == 1 { "char" } else { "chars" })
+            );
+        }
+        assert_eq!(truncated(&value, 22), r#""\n\n\n…"(+13 chars)"#);
+    }
+
+    #[test]
+    fn display_truncated_nested() {
+        let value: Value =
+            r#"{"a": [[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 2, 3], "b": {"c": {"d": 1}}}"#
+                .parse()
+                .unwrap();
+        assert_eq!(
+            truncated(&value, 50),
+            r#"{"a":[[1,2,3,4,5,6,7,8,9,10],2,3],…, +1 key}"#
+        );
+        // once the inner array is elided, the outer containers are closed right away
+        assert_eq!(
+            truncated(&value, 40),
+            r#"{"a":[[1,2,3,4,5,6,7,…, +3 items]]}"#
+        );
+        assert_eq!(truncated(&value, 20), r#"{…, +2 keys}"#);
+        assert_eq!(truncated(&value, 0), "{…}");
+
+        let value = Value::from((0..1000).collect::<Vec<i32>>());
+        assert_eq!(truncated(&value, 24), "[0,1,2,…, +997 items]");
+        for max_len in 0..100 {
+            assert!(truncated(&value, max_len).len() <= max_len + 20);
+        }
+    }
+
+    #[test]
+    fn display_truncated_deep() {
+        let arrays: Value = format!("{}1{}", "[[".repeat(60), "],1]".repeat(60))
+            .parse()
+            .unwrap();
+        let objects: Value = format!("{}1{}", r#"{"a":[{"b":"#.repeat(30), "}]}".repeat(30))
+            .parse()
+            .unwrap();
+        let strings: Value = format!("{}1{}", r#"["long string","#.repeat(100), "]".repeat(100))
+            .parse()
+            .unwrap();
+        for value in [arrays, objects, strings] {
+            for max_len in [0, 10, 50, 100, 200, 500] {
+                let s = truncated(&value, max_len);
+                assert!(s.len() <= max_len + 20, "{max_len}: {} bytes: {s}", s.len());
+            }
+        }
+    }
+}
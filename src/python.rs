@@ -0,0 +1,319 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion between `Value` and Python objects with `pyo3`.
+//!
+//! JSON values map to Python objects the same way as the `json` module does:
+//! `null`, booleans, numbers, strings, arrays and objects become `None`, `bool`, `int` or
+//! `float`, `str`, `list` and `dict`.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+
+use crate::{Builder, Value, ValueRef};
+
+impl Value {
+    /// Converts the value into a Python object.
+    ///
+    /// Objects become `dict`, arrays become `list`, and numbers become `int` or `float`.
+    pub fn to_pyobject(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.as_ref().to_pyobject(py)
+    }
+
+    /// Converts a Python object into a value with the default options.
+    ///
+    /// See [`from_pyobject_with`] for details.
+    ///
+    /// [`from_pyobject_with`]: #method.from_pyobject_with
+    pub fn from_pyobject(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::from_pyobject_with(obj, FromPyObjectOptions::default())
+    }
+
+    /// Converts a Python object into a value with options.
+    ///
+    /// `None`, `bool`, `int`, `float`, `str`, `list`, `tuple` and `dict` are supported.
+    ///
+    /// # Errors
+    ///
+    /// Raises `TypeError` for other types and for `dict` keys that are not strings.
+    /// Raises `ValueError` for NaN or infinite floats, for ints that do not fit in `i64` or
+    /// `u64` unless [`FromPyObjectOptions::big_int_as_float`] is set, and for objects nested
+    /// deeper than [`FromPyObjectOptions::max_depth`], which includes self-referential ones.
+    pub fn from_pyobject_with(
+        obj: &Bound<'_, PyAny>,
+        options: FromPyObjectOptions,
+    ) -> PyResult<Self> {
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.add_pyobject(obj, &options, 0)?;
+        Ok(builder.finish())
+    }
+}
+
+impl ValueRef<'_> {
+    /// Converts the value into a Python object.
+    ///
+    /// Objects become `dict`, arrays become `list`, and numbers become `int` or `float`.
+    pub fn to_pyobject(self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        Ok(self.to_bound(py)?.unbind())
+    }
+
+    fn to_bound(self, py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+        Ok(match self {
+            Self::Null => py.None().into_bound(py),
+            Self::Bool(b) => PyBool::new(py, b).to_owned().into_any(),
+            Self::Number(n) => {
+                let n = n.to_number();
+                if let Some(i) = n.as_i64() {
+                    i.into_pyobject(py)?.into_any()
+                } else if let Some(u) = n.as_u64() {
+                    u.into_pyobject(py)?.into_any()
+                } else {
+                    PyFloat::new(py, n.as_f64().unwrap()).into_any()
+                }
+            }
+            Self::String(s) => PyString::new(py, s).into_any(),
+            Self::Array(a) => {
+                let list = PyList::empty(py);
+                for v in a.iter() {
+                    list.append(v.to_bound(py)?)?;
+                }
+                list.into_any()
+            }
+            Self::Object(o) => {
+                let dict = PyDict::new(py);
+                for (k, v) in o.iter() {
+                    dict.set_item(k, v.to_bound(py)?)?;
+                }
+                dict.into_any()
+            }
+        })
+    }
+}
+
+/// Options for converting Python objects into values.
+///
+/// See [`Value::from_pyobject_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FromPyObjectOptions {
+    big_int_as_float: bool,
+    max_depth: usize,
+}
+
+impl Default for FromPyObjectOptions {
+    fn default() -> Self {
+        Self {
+            big_int_as_float: false,
+            max_depth: 128,
+        }
+    }
+}
+
+impl FromPyObjectOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether ints that do not fit in `i64` or `u64` are converted to floats instead of
+    /// raising an error. Defaults to `false`.
+    pub fn big_int_as_float(mut self, enable: bool) -> Self {
+        self.big_int_as_float = enable;
+        self
+    }
+
+    /// Sets the maximum nesting depth of lists and dicts. Defaults to 128.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl<W: AsMut<Vec<u8>>> Builder<W> {
+    /// Adds a Python object to the builder.
+    fn add_pyobject(
+        &mut self,
+        obj: &Bound<'_, PyAny>,
+        options: &FromPyObjectOptions,
+        depth: usize,
+    ) -> PyResult<()> {
+        if obj.is_none() {
+            self.add_null();
+        } else if let Ok(b) = obj.cast::<PyBool>() {
+            // check before `int` since `bool` is a subclass of `int`
+            self.add_bool(b.is_true());
+        } else if obj.is_instance_of::<PyInt>() {
+            if let Ok(i) = obj.extract::<i64>() {
+                self.add_i64(i);
+            } else if let Ok(u) = obj.extract::<u64>() {
+                self.add_u64(u);
+            } else if options.big_int_as_float {
+                self.add_pyfloat(obj.extract()?)?;
+            } else {
+                return Err(PyValueError::new_err(format!(
+                    "integer {} is out of the range of i64 and u64",
+                    obj.repr()?
+                )));
+            }
+        } else if let Ok(f) = obj.cast::<PyFloat>() {
+            self.add_pyfloat(f.value())?;
+        } else if let Ok(s) = obj.cast::<PyString>() {
+            self.add_string(&s.to_cow()?);
+        } else if obj.is_instance_of::<PyList>() || obj.is_instance_of::<PyTuple>() {
+            check_depth(depth, options)?;
+            self.begin_array();
+            for item in obj.try_iter()? {
+                self.add_pyobject(&item?, options, depth + 1)?;
+            }
+            self.end_array();
+        } else if let Ok(dict) = obj.cast::<PyDict>() {
+            check_depth(depth, options)?;
+            self.begin_object();
+            for (key, value) in dict.iter() {
+                let Ok(key) = key.cast::<PyString>() else {
+                    return Err(PyTypeError::new_err(format!(
+                        "dict keys must be str, not {}",
+                        key.repr()?
+                    )));
+                };
+                self.add_string(&key.to_cow()?);
+                self.add_pyobject(&value, options, depth + 1)?;
+            }
+            self.end_object();
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "object of type {} is not JSON serializable",
+                obj.get_type().name()?
+            )));
+        }
+        Ok(())
+    }
+
+    fn add_pyfloat(&mut self, f: f64) -> PyResult<()> {
+        self.try_add_f64(f)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+fn check_depth(depth: usize, options: &FromPyObjectOptions) -> PyResult<()> {
+    if depth >= options.max_depth {
+        return Err(PyValueError::new_err(format!(
+            "exceeded the maximum depth of {}, possibly due to a circular reference",
+            options.max_depth
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval<'py>(py: Python<'py>, code: &str) -> Bound<'py, PyAny> {
+        let code = std::ffi::CString::new(code).unwrap();
+        py.eval(&code, None, None).unwrap()
+    }
+
+    fn attach<R>(f: impl for<'py> FnOnce(Python<'py>) -> R) -> R {
+        Python::initialize();
+        Python::attach(f)
+    }
+
+    #[test]
+    fn roundtrip() {
+        attach(|py| {
+            let value: Value =
+                r#"{"a": [null, true, false, 0, -1, 1.5, "é"], "b": {"c": 18446744073709551615}}"#
+                    .parse()
+                    .unwrap();
+            let obj = value.to_pyobject(py).unwrap().into_bound(py);
+            let expected = eval(
+                py,
+                r#"{"a": [None, True, False, 0, -1, 1.5, "é"], "b": {"c": 2**64 - 1}}"#,
+            );
+            assert!(obj.eq(&expected).unwrap());
+            assert_eq!(Value::from_pyobject(&obj).unwrap(), value);
+
+            // tuples become arrays
+            let value = Value::from_pyobject(&eval(py, "(1, (2,), [])")).unwrap();
+            assert_eq!(value.to_string(), "[1,[2],[]]");
+        });
+    }
+
+    #[test]
+    fn big_int() {
+        attach(|py| {
+            let obj = eval(py, "[2**64]");
+            let err = Value::from_pyobject(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "ValueError: integer 18446744073709551616 is out of the range of i64 and u64"
+            );
+
+            let options = FromPyObjectOptions::new().big_int_as_float(true);
+            let value = Value::from_pyobject_with(&obj, options).unwrap();
+            assert_eq!(value.to_string(), "[1.8446744073709552e+19]");
+
+            assert!(Value::from_pyobject(&eval(py, "-2**63")).is_ok());
+            assert!(Value::from_pyobject(&eval(py, "-2**63 - 1")).is_err());
+        });
+    }
+
+    #[test]
+    fn invalid_objects() {
+        attach(|py| {
+            let err = Value::from_pyobject(&eval(py, "{'a': {1: 2}}")).unwrap_err();
+            assert_eq!(err.to_string(), "TypeError: dict keys must be str, not 1");
+
+            let err = Value::from_pyobject(&eval(py, "{(1, 'x'): 2}")).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "TypeError: dict keys must be str, not (1, 'x')"
+            );
+
+            let err = Value::from_pyobject(&eval(py, "[{1, 2}]")).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "TypeError: object of type set is not JSON serializable"
+            );
+
+            let err = Value::from_pyobject(&eval(py, "float('nan')")).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn max_depth() {
+        attach(|py| {
+            // a list containing itself
+            let obj = eval(py, "(lambda l: (l.append(l), l)[1])([])");
+            let err = Value::from_pyobject(&obj).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "ValueError: exceeded the maximum depth of 128, possibly due to a circular reference"
+            );
+
+            let obj = eval(py, "[[[1]]]");
+            let options = FromPyObjectOptions::new().max_depth(2);
+            assert!(Value::from_pyobject_with(&obj, options).is_err());
+            let options = FromPyObjectOptions::new().max_depth(3);
+            assert_eq!(
+                Value::from_pyobject_with(&obj, options)
+                    .unwrap()
+                    .to_string(),
+                "[[[1]]]"
+            );
+        });
+    }
+}
@@ -19,10 +19,10 @@ use std::fmt::{self, Display};
 use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
 use serde::ser::{self, Impossible, SerializeMap, SerializeSeq};
 
-use crate::{ArrayRef, Builder, NumberRef, ObjectRef, Value, ValueRef};
+use crate::{ArrayRef, Builder, Error, NumberRef, ObjectRef, OutOfRangeError, Value, ValueRef};
 
 /// Convert a value that `impl Serialize` into `jsonbb::Value`.
-pub fn to_value<T: ser::Serialize>(value: T) -> Result<Value, fmt::Error> {
+pub fn to_value<T: ser::Serialize>(value: T) -> Result<Value, Error> {
     let mut builder = Builder::<Vec<u8>>::new();
     value.serialize(&mut builder)?;
     Ok(builder.finish())
@@ -101,100 +101,103 @@ impl<'de, W: AsMut<Vec<u8>>> DeserializeSeed<'de> for &mut Builder<W> {
     where
         D: serde::Deserializer<'de>,
     {
-        impl<'de, W: AsMut<Vec<u8>>> Visitor<'de> for &mut Builder<W> {
-            type Value = ();
-
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("any valid JSON value")
-            }
-
-            #[inline]
-            fn visit_bool<E>(self, value: bool) -> Result<(), E> {
-                self.add_bool(value);
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_i64<E>(self, value: i64) -> Result<(), E> {
-                self.add_i64(value);
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_u64<E>(self, value: u64) -> Result<(), E> {
-                self.add_u64(value);
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_f64<E>(self, value: f64) -> Result<(), E> {
-                self.add_f64(value);
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_str<E>(self, value: &str) -> Result<(), E>
-            where
-                E: serde::de::Error,
-            {
-                self.add_string(value);
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_none<E>(self) -> Result<(), E> {
-                self.add_null();
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_some<D>(self, deserializer: D) -> Result<(), D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                self.deserialize(deserializer)
-            }
-
-            #[inline]
-            fn visit_unit<E>(self) -> Result<(), E> {
-                self.add_null();
-                Ok(())
-            }
-
-            #[inline]
-            fn visit_seq<V>(self, mut visitor: V) -> Result<(), V::Error>
-            where
-                V: SeqAccess<'de>,
-            {
-                self.begin_array();
-                while visitor.next_element_seed(&mut *self)?.is_some() {}
-                self.end_array();
-                Ok(())
-            }
-
-            fn visit_map<V>(self, mut visitor: V) -> Result<(), V::Error>
-            where
-                V: MapAccess<'de>,
-            {
-                self.begin_object();
-                while visitor.next_key_seed(&mut *self)?.is_some() {
-                    visitor.next_value_seed(&mut *self)?;
-                }
-                self.end_object();
-                Ok(())
-            }
-        }
-
+        // fail instead of panicking on documents that are too large to encode
+        self.check_size().map_err(serde::de::Error::custom)?;
         deserializer.deserialize_any(self)
     }
 }
 
+impl<'de, W: AsMut<Vec<u8>>> Visitor<'de> for &mut Builder<W> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    #[inline]
+    fn visit_bool<E>(self, value: bool) -> Result<(), E> {
+        self.add_bool(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_i64<E>(self, value: i64) -> Result<(), E> {
+        self.add_i64(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_u64<E>(self, value: u64) -> Result<(), E> {
+        self.add_u64(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_f64<E>(self, value: f64) -> Result<(), E> {
+        self.add_f64(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<(), E>
+    where
+        E: serde::de::Error,
+    {
+        self.add_string(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_none<E>(self) -> Result<(), E> {
+        self.add_null();
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_some<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    #[inline]
+    fn visit_unit<E>(self) -> Result<(), E> {
+        self.add_null();
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_seq<V>(self, mut visitor: V) -> Result<(), V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        self.begin_array();
+        while visitor.next_element_seed(&mut *self)?.is_some() {}
+        self.check_size().map_err(serde::de::Error::custom)?;
+        self.end_array();
+        Ok(())
+    }
+
+    fn visit_map<V>(self, mut visitor: V) -> Result<(), V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        self.begin_object();
+        while visitor.next_key_seed(&mut *self)?.is_some() {
+            visitor.next_value_seed(&mut *self)?;
+        }
+        self.check_size().map_err(serde::de::Error::custom)?;
+        self.end_object_checked().map_err(serde::de::Error::custom)
+    }
+}
+
 /// Jsonbb is a data format.
 // https://docs.rs/serde_json/latest/src/serde_json/ser.rs.html#59-454
 impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
     type Ok = ();
 
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     type SerializeSeq = Self;
 
@@ -236,7 +239,7 @@ impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-        self.add_i64(v.try_into().map_err(|_| invalid_number())?);
+        self.add_i64(v.try_into().map_err(|_| OutOfRangeError::signed(v))?);
         Ok(())
     }
 
@@ -261,17 +264,20 @@ impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-        self.add_u64(v.try_into().map_err(|_| invalid_number())?);
+        self.add_u64(v.try_into().map_err(|_| OutOfRangeError::unsigned(v))?);
         Ok(())
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        self.add_f64(v as _);
-        Ok(())
+        self.serialize_f64(v as _)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.add_f64(v as _);
+        if self.non_finite_as_null() && !v.is_finite() {
+            self.add_null();
+        } else {
+            self.add_f64(v);
+        }
         Ok(())
     }
 
@@ -418,7 +424,7 @@ impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeTuple for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -436,7 +442,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeTuple for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeTupleStruct for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -454,7 +460,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeTupleStruct for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeTupleVariant for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -474,7 +480,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeTupleVariant for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeMap for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
@@ -501,7 +507,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeMap for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeStruct for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
@@ -519,7 +525,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeStruct for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeStructVariant for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
@@ -539,7 +545,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeStructVariant for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeSeq for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -565,7 +571,7 @@ where
     W: AsMut<Vec<u8>>,
 {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
@@ -764,14 +770,8 @@ where
     }
 }
 
-fn key_must_be_a_string() -> std::fmt::Error {
-    // TODO: better error message
-    std::fmt::Error
-}
-
-fn invalid_number() -> std::fmt::Error {
-    // TODO: better error message
-    std::fmt::Error
+fn key_must_be_a_string() -> Error {
+    Error::Build("key must be a string".into())
 }
 
 #[cfg(test)]
@@ -926,4 +926,17 @@ mod tests {
         test_map_key(42u64);
         // test_map_key(42u128); // not supported by serde_json
     }
+
+    #[test]
+    fn to_value_error() {
+        use crate::Error;
+
+        let map = HashMap::from([((1, 2), "value")]);
+        let err = to_value(&map).unwrap_err();
+        assert!(matches!(err, Error::Build(_)));
+        assert_eq!(err.to_string(), "key must be a string");
+
+        let err = to_value(u128::MAX).unwrap_err();
+        assert!(matches!(err, Error::IntegerOutOfRange(_)));
+    }
 }
@@ -0,0 +1,154 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lightweight structural validation of JSON values.
+
+use crate::{Value, ValueRef};
+
+/// The expected shape of a JSON value.
+///
+/// This is a small subset of JSON Schema: it only describes the types of values and
+/// which object members are required.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::Shape;
+///
+/// let shape = Shape::Object(vec![
+///     ("name".into(), Shape::String, true),
+///     ("tags".into(), Shape::Array(Box::new(Shape::String)), false),
+/// ]);
+/// let value: jsonbb::Value = r#"{"name": "foo", "tags": ["a", "b"]}"#.parse().unwrap();
+/// assert!(value.matches_shape(&shape));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// Matches any value.
+    Any,
+    /// Matches `null`.
+    Null,
+    /// Matches a boolean.
+    Bool,
+    /// Matches a number.
+    Number,
+    /// Matches a string.
+    String,
+    /// Matches an array whose elements all match the given shape.
+    Array(Box<Shape>),
+    /// Matches an object with the given members.
+    ///
+    /// Each member is described by `(key, shape, required)`. A required member must be present,
+    /// and any listed member that is present must match its shape. Members that are not listed
+    /// are allowed.
+    Object(Vec<(String, Shape, bool)>),
+}
+
+impl Shape {
+    /// Returns true if the value matches this shape.
+    pub fn matches(&self, value: ValueRef<'_>) -> bool {
+        match (self, value) {
+            (Self::Any, _) => true,
+            (Self::Null, ValueRef::Null) => true,
+            (Self::Bool, ValueRef::Bool(_)) => true,
+            (Self::Number, ValueRef::Number(_)) => true,
+            (Self::String, ValueRef::String(_)) => true,
+            (Self::Array(elem), ValueRef::Array(a)) => a.iter().all(|v| elem.matches(v)),
+            (Self::Object(members), ValueRef::Object(o)) => {
+                members
+                    .iter()
+                    .all(|(key, shape, required)| match o.get(key) {
+                        Some(v) => shape.matches(v),
+                        None => !required,
+                    })
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ValueRef<'_> {
+    /// Returns true if the value matches the given shape.
+    pub fn matches_shape(self, shape: &Shape) -> bool {
+        shape.matches(self)
+    }
+}
+
+impl Value {
+    /// Returns true if the value matches the given shape.
+    ///
+    /// See [`Shape`] for how values are matched.
+    pub fn matches_shape(&self, shape: &Shape) -> bool {
+        shape.matches(self.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_shape() -> Shape {
+        Shape::Object(vec![
+            ("id".into(), Shape::Number, true),
+            ("name".into(), Shape::String, true),
+            ("email".into(), Shape::String, false),
+            (
+                "roles".into(),
+                Shape::Array(Box::new(Shape::Object(vec![(
+                    "name".into(),
+                    Shape::String,
+                    true,
+                )]))),
+                true,
+            ),
+            ("extra".into(), Shape::Any, false),
+        ])
+    }
+
+    #[test]
+    fn conforming() {
+        let value: Value = r#"{
+            "id": 1,
+            "name": "alice",
+            "roles": [{"name": "admin"}, {"name": "dev", "since": 2020}],
+            "extra": [null],
+            "unlisted": true
+        }"#
+        .parse()
+        .unwrap();
+        assert!(value.matches_shape(&user_shape()));
+    }
+
+    #[test]
+    fn non_conforming() {
+        // missing required key
+        let value: Value = r#"{"id": 1, "roles": []}"#.parse().unwrap();
+        assert!(!value.matches_shape(&user_shape()));
+
+        // optional key with wrong type
+        let value: Value = r#"{"id": 1, "name": "bob", "email": 0, "roles": []}"#
+            .parse()
+            .unwrap();
+        assert!(!value.matches_shape(&user_shape()));
+
+        // nested element with wrong type
+        let value: Value = r#"{"id": 1, "name": "bob", "roles": [{"name": null}]}"#
+            .parse()
+            .unwrap();
+        assert!(!value.matches_shape(&user_shape()));
+
+        // not an object
+        assert!(!Value::from("user").matches_shape(&user_shape()));
+    }
+}
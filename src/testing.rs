@@ -0,0 +1,138 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities for property testing with `proptest`.
+
+use proptest::prelude::*;
+
+use crate::{Builder, Value};
+
+/// Returns a strategy that generates random JSON documents.
+///
+/// Arrays and objects are nested at most `depth` levels and have at most `breadth` elements.
+/// Scalars cover every type, including edge-case numbers and unicode strings.
+///
+/// # Example
+///
+/// ```
+/// use proptest::prelude::*;
+///
+/// proptest!(|(value in jsonbb::testing::arb_value(3, 4))| {
+///     let bytes = value.as_bytes();
+///     prop_assert_eq!(jsonbb::Value::from_bytes(bytes), value);
+/// });
+/// ```
+pub fn arb_value(depth: u32, breadth: usize) -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::null()),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_filter_map("non-finite", |f| Value::try_from_f64(f).ok()),
+        prop::sample::select(&[i64::MIN, i64::MAX, -1, 0, 1][..]).prop_map(Value::from),
+        prop::sample::select(&[-0.0, 0.5, f64::MIN_POSITIVE, f64::MAX, f64::MIN][..])
+            .prop_map(Value::from),
+        any::<String>().prop_map(Value::from),
+    ];
+    let size = (breadth as u32).saturating_pow(depth).min(256);
+    leaf.prop_recursive(depth, size, breadth as u32, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..=breadth).prop_map(|elems| {
+                let mut builder = Builder::<Vec<u8>>::new();
+                builder.begin_array();
+                for v in &elems {
+                    builder.add_value(v.as_ref());
+                }
+                builder.end_array();
+                builder.finish()
+            }),
+            prop::collection::vec((any::<String>(), inner), 0..=breadth).prop_map(|entries| {
+                let mut builder = Builder::<Vec<u8>>::new();
+                builder.begin_object();
+                for (k, v) in &entries {
+                    builder.add_string(k);
+                    builder.add_value(v.as_ref());
+                }
+                builder.end_object();
+                builder.finish()
+            }),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn text_roundtrip(value in arb_value(4, 6)) {
+            let text = value.to_string();
+            prop_assert_eq!(Value::from_text(text.as_bytes()).unwrap(), value);
+        }
+
+        #[test]
+        fn float_display_matches_serde_json(
+            f in prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO,
+        ) {
+            let value = Value::from(f);
+            let expected = serde_json::Value::from(f).to_string();
+            prop_assert_eq!(&value.to_string(), &expected);
+            prop_assert_eq!(Value::from(vec![f]).to_string(), format!("[{expected}]"));
+            // floats are parsed back as floats, even if integral
+            let parsed = Value::from_text(expected.as_bytes()).unwrap();
+            prop_assert_eq!(parsed.as_f64(), Some(f));
+            prop_assert_eq!(parsed.as_i64(), None);
+        }
+
+        #[test]
+        fn bytes_roundtrip(value in arb_value(4, 6)) {
+            prop_assert_eq!(&Value::try_from_bytes(value.as_bytes()).unwrap(), &value);
+            prop_assert_eq!(Value::from_bytes(value.as_bytes()), value);
+        }
+
+        #[test]
+        fn corrupt_bytes_do_not_panic(
+            value in arb_value(3, 4),
+            edits in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..4),
+            cut in any::<prop::sample::Index>(),
+        ) {
+            let mut bytes = value.as_bytes().to_vec();
+            for (index, byte) in edits {
+                let i = index.index(bytes.len());
+                bytes[i] = byte;
+            }
+            for bytes in [&bytes[..], &bytes[cut.index(bytes.len())..]] {
+                if let Ok(value) = crate::ValueRef::try_from_bytes(bytes) {
+                    // reading a checked value must not panic
+                    let _ = value.to_string();
+                    let _ = format!("{value:?}");
+                    let _ = value.count_nodes();
+                }
+            }
+        }
+
+        #[test]
+        fn ord_is_transitive(
+            a in arb_value(2, 3),
+            b in arb_value(2, 3),
+            c in arb_value(2, 3),
+        ) {
+            let mut v = [a, b, c];
+            v.sort();
+            prop_assert!(v[0] <= v[1] && v[1] <= v[2] && v[0] <= v[2]);
+            prop_assert_eq!(v[0].cmp(&v[1]).reverse(), v[1].cmp(&v[0]));
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `TryFrom` implementations for converting `ValueRef` and `Value` into Rust types.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{ArrayRef, JsonType, ObjectRef, Value, ValueRef};
+
+/// An error returned when converting a JSON value into a Rust type fails.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::{JsonType, TryFromValueError, Value};
+///
+/// let value: Value = r#"{"count": 300, "name": "jsonbb"}"#.parse().unwrap();
+/// let count: u64 = value.get("count").unwrap().try_into().unwrap();
+/// assert_eq!(count, 300);
+///
+/// let err = u8::try_from(value.get("count").unwrap()).unwrap_err();
+/// assert_eq!(err, TryFromValueError::InvalidNumber { target: "u8" });
+///
+/// let err = bool::try_from(value.get("name").unwrap()).unwrap_err();
+/// assert_eq!(err.to_string(), "expected boolean, found string");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryFromValueError {
+    /// The value is not of the expected type.
+    TypeMismatch {
+        /// The expected type.
+        expected: JsonType,
+        /// The actual type of the value.
+        found: JsonType,
+    },
+    /// The value is a number, but it can not be represented by the target type without loss,
+    /// e.g. it is out of range or has a fractional part.
+    InvalidNumber {
+        /// The name of the target type.
+        target: &'static str,
+    },
+}
+
+impl TryFromValueError {
+    fn mismatch(expected: JsonType, value: ValueRef<'_>) -> Self {
+        Self::TypeMismatch {
+            expected,
+            found: value.json_type(),
+        }
+    }
+}
+
+impl fmt::Display for TryFromValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            Self::InvalidNumber { target } => {
+                write!(f, "number can not be represented as {target}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TryFromValueError {}
+
+/// Implements `TryFrom<Value>` by delegating to `TryFrom<ValueRef>`.
+macro_rules! impl_try_from_value {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Value> for $ty {
+                type Error = TryFromValueError;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    Self::try_from(value.as_ref())
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `TryFrom<ValueRef>` for integer types with range checking.
+macro_rules! impl_try_from_integer {
+    ($($ty:ty => $as:ident),*) => {
+        $(
+            impl TryFrom<ValueRef<'_>> for $ty {
+                type Error = TryFromValueError;
+
+                fn try_from(value: ValueRef<'_>) -> Result<Self, Self::Error> {
+                    let ValueRef::Number(n) = value else {
+                        return Err(TryFromValueError::mismatch(JsonType::Number, value));
+                    };
+                    n.$as()
+                        .and_then(|i| <$ty>::try_from(i).ok())
+                        .ok_or(TryFromValueError::InvalidNumber {
+                            target: stringify!($ty),
+                        })
+                }
+            }
+
+            impl_try_from_value!($ty);
+        )*
+    };
+}
+
+impl_try_from_integer!(
+    u8 => as_u64, u16 => as_u64, u32 => as_u64, u64 => as_u64, usize => as_u64,
+    i8 => as_i64, i16 => as_i64, i32 => as_i64, i64 => as_i64, isize => as_i64
+);
+
+impl TryFrom<ValueRef<'_>> for f64 {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'_>) -> Result<Self, Self::Error> {
+        value
+            .as_f64()
+            .ok_or_else(|| TryFromValueError::mismatch(JsonType::Number, value))
+    }
+}
+
+impl TryFrom<ValueRef<'_>> for bool {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'_>) -> Result<Self, Self::Error> {
+        value
+            .as_bool()
+            .ok_or_else(|| TryFromValueError::mismatch(JsonType::Boolean, value))
+    }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for &'a str {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'a>) -> Result<Self, Self::Error> {
+        value
+            .as_str()
+            .ok_or_else(|| TryFromValueError::mismatch(JsonType::String, value))
+    }
+}
+
+impl TryFrom<ValueRef<'_>> for String {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'_>) -> Result<Self, Self::Error> {
+        <&str>::try_from(value).map(String::from)
+    }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for ArrayRef<'a> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'a>) -> Result<Self, Self::Error> {
+        value
+            .as_array()
+            .ok_or_else(|| TryFromValueError::mismatch(JsonType::Array, value))
+    }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for ObjectRef<'a> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'a>) -> Result<Self, Self::Error> {
+        value
+            .as_object()
+            .ok_or_else(|| TryFromValueError::mismatch(JsonType::Object, value))
+    }
+}
+
+impl TryFrom<ValueRef<'_>> for Vec<Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'_>) -> Result<Self, Self::Error> {
+        let array = ArrayRef::try_from(value)?;
+        Ok(array.iter().map(Value::from).collect())
+    }
+}
+
+impl TryFrom<ValueRef<'_>> for BTreeMap<String, Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: ValueRef<'_>) -> Result<Self, Self::Error> {
+        let object = ObjectRef::try_from(value)?;
+        Ok(object
+            .iter()
+            .map(|(k, v)| (k.to_owned(), Value::from(v)))
+            .collect())
+    }
+}
+
+impl_try_from_value!(f64, bool, String, Vec<Value>, BTreeMap<String, Value>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_value() {
+        let value: Value = r#"{"n": -3, "big": 300, "f": 1.5, "b": true, "s": "x", "a": [1, "y"], "o": {"k": null}}"#
+            .parse()
+            .unwrap();
+        let get = |key| value.get(key).unwrap();
+
+        assert_eq!(i64::try_from(get("n")), Ok(-3));
+        assert_eq!(i8::try_from(get("n")), Ok(-3));
+        assert_eq!(u16::try_from(get("big")), Ok(300));
+        assert_eq!(f64::try_from(get("f")), Ok(1.5));
+        assert_eq!(f64::try_from(get("n")), Ok(-3.0));
+        assert_eq!(bool::try_from(get("b")), Ok(true));
+        assert_eq!(<&str>::try_from(get("s")), Ok("x"));
+        assert_eq!(String::try_from(Value::from(get("s"))), Ok("x".to_string()));
+        assert_eq!(ArrayRef::try_from(get("a")).unwrap().len(), 2);
+        assert_eq!(ObjectRef::try_from(get("o")).unwrap().len(), 1);
+
+        let array = Vec::<Value>::try_from(Value::from(get("a"))).unwrap();
+        assert_eq!(array, [Value::from(1), Value::from("y")]);
+        let object = BTreeMap::<String, Value>::try_from(get("o")).unwrap();
+        assert_eq!(object.get("k"), Some(&Value::null()));
+
+        // out of range or not an integer
+        let invalid = |target| TryFromValueError::InvalidNumber { target };
+        assert_eq!(u64::try_from(get("n")).unwrap_err(), invalid("u64"));
+        assert_eq!(u8::try_from(get("big")).unwrap_err(), invalid("u8"));
+        assert_eq!(i8::try_from(get("big")).unwrap_err(), invalid("i8"));
+        assert_eq!(i64::try_from(get("f")).unwrap_err(), invalid("i64"));
+
+        // type mismatch
+        let mismatch = |expected, found| TryFromValueError::TypeMismatch { expected, found };
+        assert_eq!(
+            u32::try_from(get("s")).unwrap_err(),
+            mismatch(JsonType::Number, JsonType::String)
+        );
+        assert_eq!(
+            bool::try_from(get("o")).unwrap_err(),
+            mismatch(JsonType::Boolean, JsonType::Object)
+        );
+        assert_eq!(
+            String::try_from(get("b")).unwrap_err(),
+            mismatch(JsonType::String, JsonType::Boolean)
+        );
+        assert_eq!(
+            Vec::<Value>::try_from(Value::null()).unwrap_err(),
+            mismatch(JsonType::Array, JsonType::Null)
+        );
+        assert_eq!(
+            BTreeMap::<String, Value>::try_from(get("a")).unwrap_err(),
+            mismatch(JsonType::Object, JsonType::Array)
+        );
+    }
+}
@@ -32,6 +32,26 @@ impl Value {
         Self::from(())
     }
 
+    /// Returns a reference to a shared `null` value.
+    ///
+    /// The value is allocated once on first use, so later calls never allocate. This is useful
+    /// for handing out nulls by reference, e.g. as a fallback for missing values.
+    ///
+    /// Note that a `Value` owns a heap buffer, so a `const` null can't be provided. Use
+    /// [`ValueRef::Null`] if a borrowed value suffices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value = jsonbb::Value::null_static();
+    /// assert!(value.is_null());
+    /// assert!(std::ptr::eq(value, jsonbb::Value::null_static()));
+    /// ```
+    pub fn null_static() -> &'static Self {
+        static NULL: std::sync::OnceLock<Value> = std::sync::OnceLock::new();
+        NULL.get_or_init(Self::null)
+    }
+
     /// Creates a new JSON array from an iterator of values.
     pub fn array<'a>(iter: impl IntoIterator<Item = ValueRef<'a>>) -> Self {
         Self::from_builder(0, |b| {
@@ -55,20 +75,145 @@ impl Value {
         })
     }
 
+    /// Creates a JSON number from an f64.
+    ///
+    /// Unlike `From<f64>`, this returns an error instead of panicking if the value is NaN
+    /// or infinite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(jsonbb::Value::try_from_f64(1.5).unwrap().as_f64(), Some(1.5));
+    /// assert!(jsonbb::Value::try_from_f64(f64::NAN).is_err());
+    /// ```
+    pub fn try_from_f64(v: f64) -> Result<Self, NonFiniteError> {
+        let mut builder = Builder::with_capacity(1 + 8 + 4);
+        builder.try_add_f64(v)?;
+        Ok(builder.finish())
+    }
+
     /// Deserialize an instance of `Value` from bytes of JSON text.
-    pub fn from_text(json: &[u8]) -> serde_json::Result<Self> {
+    ///
+    /// If an object has duplicate keys, the last value is used.
+    /// Use [`from_text_with`] to configure this behavior.
+    ///
+    /// Floats are parsed by `serde_json`, which may be off by one unit in the last place
+    /// unless its `float_roundtrip` feature is enabled. Enable it in your own `Cargo.toml` if
+    /// floats must round-trip exactly through text.
+    ///
+    /// On failure, the [`Error::Parse`] error reports the [`line`] and [`column`] where parsing
+    /// stopped.
+    ///
+    /// [`from_text_with`]: #method.from_text_with
+    /// [`line`]: serde_json::Error::line
+    /// [`column`]: serde_json::Error::column
+    pub fn from_text(json: &[u8]) -> Result<Self, Error> {
+        Self::from_text_with(json, ParseOptions::default())
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text with options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{DuplicateKeyPolicy, ParseOptions, Value};
+    ///
+    /// let json = br#"{"a": 1, "a": 2}"#;
+    /// let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::FirstWins);
+    /// assert_eq!(Value::from_text_with(json, options).unwrap().to_string(), r#"{"a":1}"#);
+    ///
+    /// let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+    /// assert!(Value::from_text_with(json, options).is_err());
+    /// ```
+    pub fn from_text_with(json: &[u8], options: ParseOptions) -> Result<Self, Error> {
         use ::serde::de::DeserializeSeed;
 
         let mut builder = Builder::with_capacity(json.len());
+        builder.set_duplicate_keys(options.duplicate_keys);
         let mut deserializer = serde_json::Deserializer::from_slice(json);
-        builder.deserialize(&mut deserializer)?;
+        if let Err(e) = builder.deserialize(&mut deserializer) {
+            return Err(builder.check_size().err().unwrap_or(e.into()));
+        }
         deserializer.end()?;
         Ok(builder.finish())
     }
 
-    /// Deserialize an instance of `Value` from bytes of JSON text.
+    /// Deserialize the first `Value` from bytes of JSON text.
+    ///
+    /// Returns the value and the number of bytes consumed. Any bytes after the value are
+    /// left unparsed, so this can be used to read a sequence of concatenated values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let json = b"1 [2] 3";
+    /// let (value, len) = jsonbb::Value::from_text_prefix(json).unwrap();
+    /// assert_eq!(value.to_string(), "1");
+    /// assert_eq!(len, 1);
+    ///
+    /// let (value, _) = jsonbb::Value::from_text_prefix(&json[len..]).unwrap();
+    /// assert_eq!(value.to_string(), "[2]");
+    /// ```
+    pub fn from_text_prefix(json: &[u8]) -> Result<(Self, usize), Error> {
+        /// A wrapper to drive the builder from `StreamDeserializer`.
+        struct Parsed(Value);
+
+        impl<'de> ::serde::Deserialize<'de> for Parsed {
+            fn deserialize<D: ::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                use ::serde::de::DeserializeSeed;
+
+                let mut builder = Builder::<Vec<u8>>::new();
+                builder.deserialize(deserializer)?;
+                Ok(Parsed(builder.finish()))
+            }
+        }
+
+        let mut stream = serde_json::Deserializer::from_slice(json).into_iter::<Parsed>();
+        match stream.next() {
+            Some(Ok(Parsed(value))) => Ok((value, stream.byte_offset())),
+            Some(Err(e)) => Err(e.into()),
+            // the input is empty or only whitespace, report the EOF error
+            None => Err(serde_json::from_slice::<::serde::de::IgnoredAny>(json)
+                .unwrap_err()
+                .into()),
+        }
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text with comments and trailing
+    /// commas.
+    ///
+    /// This accepts a small subset of JSON5 that is common in configuration files:
+    ///
+    /// - Line comments starting with `//` and ending at the end of the line.
+    /// - Block comments between `/*` and `*/`. They can span lines but cannot be nested.
+    /// - A trailing comma after the last element of an array or the last member of an object.
+    ///
+    /// Other JSON5 features, such as unquoted keys, single-quoted strings, hexadecimal numbers,
+    /// `NaN` or `Infinity`, are not supported and are reported as syntax errors.
+    /// Comments and trailing commas are replaced by whitespace before parsing, so the line and
+    /// column of an error refer to the original text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let json = br#"{
+    ///     // line comment
+    ///     "a": [1, 2, /* block comment */ 3,],
+    /// }"#;
+    /// let value = jsonbb::Value::from_text_lenient(json).unwrap();
+    /// assert_eq!(value.to_string(), r#"{"a":[1,2,3]}"#);
+    /// ```
+    pub fn from_text_lenient(json: &[u8]) -> Result<Self, Error> {
+        Self::from_text(&strip_comments_and_trailing_commas(json))
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text with `simd-json`.
+    ///
+    /// On failure, the original `simd-json` error is returned as [`Error::Simd`].
     #[cfg(feature = "simd-json")]
-    pub fn from_text_mut(json: &mut [u8]) -> simd_json::Result<Self> {
+    pub fn from_text_mut(json: &mut [u8]) -> Result<Self, Error> {
         use ::serde::de::DeserializeSeed;
 
         let mut builder = Builder::with_capacity(json.len());
@@ -78,22 +223,57 @@ impl Value {
     }
 
     /// Creates a JSON `Value` from bytes of jsonbb encoding.
+    ///
+    /// The bytes are not checked. See [`ValueRef::from_bytes`] for details, and use
+    /// [`try_from_bytes`] for bytes from an untrusted source.
+    ///
+    /// [`try_from_bytes`]: #method.try_from_bytes
     pub fn from_bytes(bytes: &[u8]) -> Self {
         Self {
             buffer: bytes.into(),
         }
     }
 
+    /// Creates a JSON `Value` from bytes of jsonbb encoding, checking that they are valid.
+    ///
+    /// See [`ValueRef::try_from_bytes`] for details.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        ValueRef::try_from_bytes(bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
     /// Returns a reference to the value.
     pub fn as_ref(&self) -> ValueRef<'_> {
         ValueRef::from_bytes(&self.buffer)
     }
 
     /// Returns the value as bytes.
+    ///
+    /// The bytes are at least 4 bytes long and end with the [`Entry`] of the value. They have
+    /// no alignment requirement, since all fields are read with unaligned loads, so they can be
+    /// stored anywhere and read back with [`ValueRef::from_bytes`] or [`Value::from_bytes`].
     pub fn as_bytes(&self) -> &[u8] {
         &self.buffer
     }
 
+    /// Copies the encoded bytes of the value into `buf`, replacing its contents.
+    ///
+    /// This reuses the allocation of `buf`, which is useful to avoid allocating for each
+    /// clone in a hot loop. The copy can be read with [`ValueRef::from_bytes`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"a": [1, 2]}"#.parse().unwrap();
+    /// let mut buf = Vec::new();
+    /// value.clone_into_buffer(&mut buf);
+    /// assert_eq!(jsonbb::ValueRef::from_bytes(&buf), value.as_ref());
+    /// ```
+    pub fn clone_into_buffer(&self, buf: &mut Vec<u8>) {
+        buf.clear();
+        buf.extend_from_slice(&self.buffer);
+    }
+
     /// If the value is `null`, returns `()`. Returns `None` otherwise.
     ///
     /// # Example
@@ -190,6 +370,30 @@ impl Value {
         self.as_ref().as_object()
     }
 
+    /// If the value is an array, returns the associated array. Returns an empty array otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value = jsonbb::Value::from(());
+    /// assert!(value.as_array_or_empty().is_empty());
+    /// ```
+    pub fn as_array_or_empty(&self) -> ArrayRef<'_> {
+        self.as_ref().as_array_or_empty()
+    }
+
+    /// If the value is an object, returns the associated map. Returns an empty object otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value = jsonbb::Value::from(());
+    /// assert!(value.as_object_or_empty().is_empty());
+    /// ```
+    pub fn as_object_or_empty(&self) -> ObjectRef<'_> {
+        self.as_ref().as_object_or_empty()
+    }
+
     /// Returns true if the value is a null. Returns false otherwise.
     ///
     /// # Example
@@ -204,6 +408,13 @@ impl Value {
         self.as_ref().is_null()
     }
 
+    /// Returns true if the value equals [`Value::default()`], which is `null`.
+    ///
+    /// This is useful with `#[serde(skip_serializing_if = "Value::is_default")]`.
+    pub fn is_default(&self) -> bool {
+        self.is_null()
+    }
+
     /// Returns true if the value is a boolean. Returns false otherwise.
     ///
     /// # Example
@@ -298,6 +509,20 @@ impl Value {
         self.as_ref().is_object()
     }
 
+    /// Returns the type of the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::JsonType;
+    ///
+    /// assert_eq!(jsonbb::Value::from(1).json_type(), JsonType::Number);
+    /// assert_eq!(jsonbb::Value::array([]).json_type().to_string(), "array");
+    /// ```
+    pub fn json_type(&self) -> JsonType {
+        self.as_ref().json_type()
+    }
+
     /// Returns the capacity of the internal buffer, in bytes.
     pub fn capacity(&self) -> usize {
         self.buffer.len()
@@ -325,6 +550,41 @@ impl Value {
         index.index_into(self.as_ref())
     }
 
+    /// Returns the values associated with several keys of a JSON object at once.
+    ///
+    /// Returns all `None` if the value is not an object.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let object: jsonbb::Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+    /// let [a, b, c] = object.get_many(["a", "b", "c"]);
+    /// assert_eq!(a.unwrap().as_u64(), Some(1));
+    /// assert_eq!(b.unwrap().as_u64(), Some(2));
+    /// assert!(c.is_none());
+    /// ```
+    pub fn get_many<const N: usize>(&self, keys: [&str; N]) -> [Option<ValueRef<'_>>; N] {
+        match self.as_object() {
+            Some(o) => o.get_many(keys),
+            None => [None; N],
+        }
+    }
+
+    /// Returns the value of the first key that is equal to `key` ignoring ASCII case.
+    ///
+    /// Returns `None` if the value is not an object.
+    /// See [`ObjectRef::get_ignore_ascii_case`] for details.
+    pub fn get_ignore_ascii_case(&self, key: &str) -> Option<ValueRef<'_>> {
+        self.as_object()?.get_ignore_ascii_case(key)
+    }
+
+    /// Index into a JSON array or object, failing if the value is of the wrong type.
+    ///
+    /// See [`ValueRef::try_get`] for details.
+    pub fn try_get(&self, index: impl Index) -> Result<Option<ValueRef<'_>>, Error> {
+        self.as_ref().try_get(index)
+    }
+
     /// Looks up a value by a JSON Pointer.
     ///
     /// JSON Pointer defines a string syntax for identifying a specific value
@@ -355,13 +615,104 @@ impl Value {
         self.as_ref().pointer(pointer)
     }
 
+    /// Looks up a value by a dotted path like `a.b.0.c`.
+    ///
+    /// See [`ValueRef::get_dotted`] for details.
+    pub fn get_dotted(&self, path: &str) -> Option<ValueRef<'_>> {
+        self.as_ref().get_dotted(path)
+    }
+
+    /// Returns the first candidate that is not null, like SQL `COALESCE`.
+    ///
+    /// Returns `None` if all candidates are null or there are no candidates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{Value, ValueRef};
+    ///
+    /// let user: Value = r#"{"nickname": null, "name": "alice"}"#.parse().unwrap();
+    /// let name = Value::coalesce([user.get("nickname"), user.get("name")].into_iter().flatten());
+    /// assert_eq!(name, Some(ValueRef::String("alice")));
+    ///
+    /// assert_eq!(Value::coalesce([ValueRef::Null]), None);
+    /// ```
+    pub fn coalesce<'a>(
+        candidates: impl IntoIterator<Item = ValueRef<'a>>,
+    ) -> Option<ValueRef<'a>> {
+        candidates.into_iter().find(|v| !v.is_null())
+    }
+
+    /// Serializes the value as compact JSON text, formatting floats with a fixed number of
+    /// decimal places.
+    ///
+    /// Only numbers stored as floats are affected. Integers and other values are serialized
+    /// as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"pi": 3.14159, "n": 42}"#.parse().unwrap();
+    /// assert_eq!(value.to_string_with_float_precision(2), r#"{"n":42,"pi":3.14}"#);
+    /// ```
+    pub fn to_string_with_float_precision(&self, digits: usize) -> String {
+        self.as_ref().to_string_with_float_precision(digits)
+    }
+
+    /// Serializes the value as pretty-printed JSON text.
+    ///
+    /// See [`ValueRef::to_string_pretty`] for details.
+    pub fn to_string_pretty(&self) -> String {
+        self.as_ref().to_string_pretty()
+    }
+
+    /// Serializes the value as compact JSON text into a byte vector.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+
+    /// Serializes the value as pretty-printed JSON text into a byte vector.
+    pub fn to_vec_pretty(&self) -> Vec<u8> {
+        self.as_ref().to_vec_pretty()
+    }
+
+    /// Appends the compact JSON text of the value to a byte buffer.
+    ///
+    /// See [`ValueRef::write_to`] for details.
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        self.as_ref().write_to(out)
+    }
+
+    /// Serializes the value as compact JSON text into a `SmallVec`.
+    ///
+    /// See [`ValueRef::serialize_to_smallvec`] for details.
+    pub fn serialize_to_smallvec(&self) -> smallvec::SmallVec<[u8; 64]> {
+        self.as_ref().serialize_to_smallvec()
+    }
+
+    /// Returns the total number of values in the document, including containers and the
+    /// value itself. Object keys are not counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"[1, [2, 3]]"#.parse().unwrap();
+    /// assert_eq!(value.count_nodes(), 5);
+    /// ```
+    pub fn count_nodes(&self) -> usize {
+        self.as_ref().count_nodes()
+    }
+
     /// Push a value into a JSON array.
     ///
     /// This function is `O(N)` where N is the number of elements in the array.
+    /// To build an array element by element, use [`ArrayBuilder`] instead.
     ///
     /// # Panics
     ///
-    /// Panics if the value is not an array.
+    /// Panics if the value is not an array. Use [`try_array_push`] to handle this case.
+    ///
+    /// [`try_array_push`]: #method.try_array_push
     ///
     /// # Example
     /// ```
@@ -374,70 +725,655 @@ impl Value {
     /// assert_eq!(array.to_string(), r#"[1,null,2,"str",[],{}]"#);
     /// ```
     pub fn array_push(&mut self, value: ValueRef<'_>) {
-        let len = self.as_array().expect("not array").len();
-        // The offset to insert the value.
+        if let Err(e) = self.try_array_push(value) {
+            panic!("{e}");
+        }
+    }
+
+    /// Push a value into a JSON array.
+    ///
+    /// Returns an error without modifying the value if it is not an array, or if the result
+    /// would be too large to encode.
+    ///
+    /// # Example
+    /// ```
+    /// use jsonbb::{Error, JsonType, Value};
+    ///
+    /// let mut object: Value = "{}".parse().unwrap();
+    /// let err = object.try_array_push(Value::from(1).as_ref()).unwrap_err();
+    /// assert!(matches!(err, Error::InvalidType { expected: JsonType::Array, found: JsonType::Object }));
+    /// ```
+    pub fn try_array_push(&mut self, value: ValueRef<'_>) -> Result<(), Error> {
+        self.try_array_append([value])
+    }
+
+    /// Appends values to a JSON array in place, without rebuilding the existing elements.
+    ///
+    /// Returns an error without modifying the value if it is not an array, or if the result
+    /// would be too large to encode.
+    fn try_array_append<'a>(
+        &mut self,
+        values: impl IntoIterator<Item = ValueRef<'a>>,
+    ) -> Result<(), Error> {
+        let len = self.as_array_or_err()?.len();
+        let values: Vec<_> = values.into_iter().collect();
+        let added = values.iter().map(|v| v.capacity() + 4).sum::<usize>();
+        check_size(self.buffer.len() + added)?;
+        // The offset to insert the values.
         let offset = self.buffer.len() - 4 - 4 - 4 - 4 * len;
         let mut buffer = std::mem::take(&mut self.buffer).into_vec();
-        // reserve space for the value + its entry
-        buffer.reserve_exact(value.capacity() + 4);
-        // remove tailing (len, size, entry)
-        buffer.truncate(buffer.len() - 12);
-        // insert the value
-        buffer.splice(offset..offset, value.as_slice().iter().copied());
-        // push the entry
-        buffer.put_slice(value.make_entry(offset).as_bytes());
+        // reserve space for the values + their entries
+        buffer.reserve_exact(added);
+        // remove the existing entries and tailing (len, size, entry)
+        let entries = buffer[offset..buffer.len() - 12].to_vec();
+        buffer.truncate(offset);
+        // append the values
+        let mut new_entries = Vec::with_capacity(values.len());
+        for value in &values {
+            new_entries.push(value.make_entry(buffer.len()));
+            buffer.put_slice(value.as_slice());
+        }
+        // push the entries
+        buffer.put_slice(&entries);
+        for entry in new_entries {
+            buffer.put_slice(entry.as_bytes());
+        }
         // push (len, size, entry)
-        buffer.put_u32_ne((len + 1) as u32);
+        buffer.put_u32_ne((len + values.len()) as u32);
         buffer.put_u32_ne((buffer.len() + 4) as u32);
         buffer.put_slice(Entry::array(buffer.len()).as_bytes());
         // store the buffer
         self.buffer = buffer.into();
+        Ok(())
     }
 
-    fn from_builder(capacity: usize, f: impl FnOnce(&mut Builder)) -> Self {
-        let mut builder = Builder::with_capacity(capacity);
-        f(&mut builder);
-        builder.finish()
+    /// Removes the last element from a JSON array and returns it, or `None` if it is empty.
+    ///
+    /// This function is `O(N)` where N is the size of the array in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an array. Use [`try_array_pop`] to handle this case.
+    ///
+    /// [`try_array_pop`]: #method.try_array_pop
+    ///
+    /// # Example
+    /// ```
+    /// let mut array: jsonbb::Value = "[1, [2]]".parse().unwrap();
+    /// assert_eq!(array.array_pop().unwrap().to_string(), "[2]");
+    /// assert_eq!(array.array_pop().unwrap().to_string(), "1");
+    /// assert_eq!(array.array_pop(), None);
+    /// assert_eq!(array.to_string(), "[]");
+    /// ```
+    pub fn array_pop(&mut self) -> Option<Value> {
+        match self.try_array_pop() {
+            Ok(value) => value,
+            Err(e) => panic!("{e}"),
+        }
     }
-}
 
-impl fmt::Debug for Value {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.as_ref().fmt(f)
+    /// Removes the last element from a JSON array and returns it, or `None` if it is empty.
+    ///
+    /// Returns an error without modifying the value if it is not an array.
+    pub fn try_array_pop(&mut self) -> Result<Option<Value>, Error> {
+        let array = self.as_array_or_err()?;
+        let Some(len) = array.len().checked_sub(1) else {
+            return Ok(None);
+        };
+        let last = array.get(len).unwrap().to_owned();
+        let new = Self::from_builder(self.capacity() - last.capacity(), |b| {
+            b.begin_array();
+            for v in array.iter().take(len) {
+                b.add_value(v);
+            }
+            b.end_array();
+        });
+        *self = new;
+        Ok(Some(last))
     }
-}
 
-/// Display a JSON value as a string.
-impl fmt::Display for Value {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.as_ref().fmt(f)
+    /// Replaces a range of elements in a JSON array with new elements, in a single rebuild.
+    ///
+    /// This works like [`Vec::splice`], except that the removed elements are not returned.
+    /// The number of new elements may differ from the length of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an array, if the start of the range is greater than the end,
+    /// or if the end is greater than the length of the array. Use [`try_splice_array`] to handle
+    /// a value that is not an array.
+    ///
+    /// [`try_splice_array`]: #method.try_splice_array
+    ///
+    /// # Example
+    /// ```
+    /// let mut array: jsonbb::Value = "[1, 2, 3, 4]".parse().unwrap();
+    /// let (x, y) = (jsonbb::Value::from("x"), jsonbb::Value::from("y"));
+    /// array.splice_array(1..3, [x.as_ref(), y.as_ref(), x.as_ref()]);
+    /// assert_eq!(array.to_string(), r#"[1,"x","y","x",4]"#);
+    /// ```
+    pub fn splice_array<'a>(
+        &mut self,
+        range: std::ops::Range<usize>,
+        replacement: impl IntoIterator<Item = ValueRef<'a>>,
+    ) {
+        if let Some(array) = self.as_array() {
+            assert!(
+                range.start <= range.end,
+                "slice index starts at {} but ends at {}",
+                range.start,
+                range.end
+            );
+            assert!(
+                range.end <= array.len(),
+                "range end index {} out of range for array of length {}",
+                range.end,
+                array.len()
+            );
+        }
+        if let Err(e) = self.try_splice_array(range, replacement) {
+            panic!("{e}");
+        }
     }
-}
 
-/// # Example
-///
-/// ```
-/// let a: jsonbb::Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
-/// let b: jsonbb::Value = r#"{"b": 2, "a": 1.0}"#.parse().unwrap();
-/// assert_eq!(a, b);
-/// ```
-impl PartialEq for Value {
-    fn eq(&self, other: &Self) -> bool {
-        self.as_ref().eq(&other.as_ref())
+    /// Replaces a range of elements in a JSON array with new elements, in a single rebuild.
+    ///
+    /// Returns an error without modifying the value if it is not an array, if the range is
+    /// invalid, or if the result would be too large to encode. An end greater than the length
+    /// of the array, or a start greater than the end, is reported as [`Error::OutOfBounds`].
+    ///
+    /// # Example
+    /// ```
+    /// use jsonbb::{Error, Value};
+    ///
+    /// let mut array: Value = "[1, 2]".parse().unwrap();
+    /// let err = array.try_splice_array(1..3, []).unwrap_err();
+    /// assert!(matches!(err, Error::OutOfBounds { index: 3, len: 2 }));
+    /// ```
+    pub fn try_splice_array<'a>(
+        &mut self,
+        range: std::ops::Range<usize>,
+        replacement: impl IntoIterator<Item = ValueRef<'a>>,
+    ) -> Result<(), Error> {
+        let array = self.as_array_or_err()?;
+        if range.end > array.len() {
+            return Err(Error::OutOfBounds {
+                index: range.end,
+                len: array.len(),
+            });
+        }
+        if range.start > range.end {
+            return Err(Error::OutOfBounds {
+                index: range.start,
+                len: range.end,
+            });
+        }
+        let replacement: Vec<_> = replacement.into_iter().collect();
+        let size = replacement
+            .iter()
+            .fold(self.capacity(), |size, v| size + v.capacity() + 4);
+        check_size(size)?;
+        let new = Self::from_builder(size, |b| {
+            b.begin_array();
+            for v in array.iter().take(range.start) {
+                b.add_value(v);
+            }
+            for v in replacement {
+                b.add_value(v);
+            }
+            for v in array.iter().skip(range.end) {
+                b.add_value(v);
+            }
+            b.end_array();
+        });
+        *self = new;
+        Ok(())
     }
-}
 
-impl Eq for Value {}
-
-impl PartialOrd for Value {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// Returns a copy of the value with every string longer than `max_len` bytes truncated.
+    ///
+    /// Truncated strings are cut at the nearest UTF-8 character boundary at or below `max_len`
+    /// and suffixed with `…`. Object keys are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"msg": "hello world", "id": 1}"#.parse().unwrap();
+    /// assert_eq!(value.truncate_strings(5).to_string(), r#"{"id":1,"msg":"hello…"}"#);
+    /// ```
+    pub fn truncate_strings(&self, max_len: usize) -> Value {
+        Self::from_builder(self.capacity(), |b| {
+            b.add_value_with_truncated_strings(self.as_ref(), max_len)
+        })
     }
-}
 
-/// Compare two JSON values.
-///
-/// The ordering is defined as follows:
+    /// Returns a copy of the value with the values of matching keys replaced by `"***"`.
+    ///
+    /// Every object member whose key matches `should_redact` is redacted, at any depth.
+    /// Redacted values are not searched further. This is useful for logging values that may
+    /// contain secrets or personal data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"user": "alice", "auth": {"password": "x"}}"#.parse().unwrap();
+    /// assert_eq!(
+    ///     value.redact(|key| key == "password").to_string(),
+    ///     r#"{"auth":{"password":"***"},"user":"alice"}"#
+    /// );
+    /// ```
+    pub fn redact(&self, should_redact: impl Fn(&str) -> bool) -> Value {
+        Self::from_builder(self.capacity(), |b| {
+            b.add_value_with_redacted_keys(self.as_ref(), &should_redact)
+        })
+    }
+
+    /// Returns a copy of the value clamped to the given limits.
+    ///
+    /// Arrays and objects nested deeper than [`PruneLimits::max_depth`] are replaced by the
+    /// string `"<truncated>"`, arrays and objects keep only their first elements and members,
+    /// and long strings are cut and suffixed with `…`. Object keys are left unchanged.
+    ///
+    /// The result is built in a single pass. Subtrees that are small enough to be within all
+    /// limits are copied as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{PruneLimits, Value};
+    ///
+    /// let value: Value = r#"{"a": [1, 2, 3], "b": {"c": {"d": 1}}, "s": "hello"}"#.parse().unwrap();
+    /// let limits = PruneLimits::new()
+    ///     .max_depth(2)
+    ///     .max_array_items(2)
+    ///     .max_string_len(3);
+    /// assert_eq!(
+    ///     value.prune(&limits).to_string(),
+    ///     r#"{"a":[1,2],"b":{"c":"<truncated>"},"s":"hel…"}"#
+    /// );
+    /// ```
+    pub fn prune(&self, limits: &PruneLimits) -> Value {
+        Self::from_builder(self.capacity(), |b| {
+            b.add_value_with_limits(self.as_ref(), limits, 0)
+        })
+    }
+
+    /// Rewrites every float with an integral value (e.g. `2.0`) into an integer.
+    ///
+    /// Numbers like `2.0` and `2` already compare equal, but serialize differently. This forces
+    /// the integer form, which is useful for canonicalization and also takes less space.
+    /// Floats outside the range of `i64` and `u64` are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut value: jsonbb::Value = r#"[2.0, 2.5, {"a": -1e3}]"#.parse().unwrap();
+    /// value.normalize_numbers();
+    /// assert_eq!(value.to_string(), r#"[2,2.5,{"a":-1000}]"#);
+    /// ```
+    pub fn normalize_numbers(&mut self) {
+        *self = Self::from_builder(self.capacity(), |b| {
+            b.add_value_with_normalized_numbers(self.as_ref())
+        });
+    }
+
+    /// Inserts a key-value pair into a JSON object.
+    ///
+    /// If the object already has this key, the value is replaced.
+    /// This function is `O(N)` where N is the number of members in the object. To insert many
+    /// pairs, use [`object_insert_many`] instead.
+    ///
+    /// [`object_insert_many`]: #method.object_insert_many
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an object. Use [`try_object_insert`] to handle this case.
+    ///
+    /// [`try_object_insert`]: #method.try_object_insert
+    ///
+    /// # Example
+    /// ```
+    /// let mut object: jsonbb::Value = r#"{"a": 1}"#.parse().unwrap();
+    /// object.object_insert("b", jsonbb::Value::from(2).as_ref());
+    /// assert_eq!(object.to_string(), r#"{"a":1,"b":2}"#);
+    /// ```
+    pub fn object_insert(&mut self, key: &str, value: ValueRef<'_>) {
+        self.object_insert_many([(key, value)]);
+    }
+
+    /// Inserts a key-value pair into a JSON object.
+    ///
+    /// Returns an error without modifying the value if it is not an object, or if the result
+    /// would be too large to encode.
+    pub fn try_object_insert(&mut self, key: &str, value: ValueRef<'_>) -> Result<(), Error> {
+        self.try_object_insert_many([(key, value)])
+    }
+
+    /// Inserts multiple key-value pairs into a JSON object with a single rebuild.
+    ///
+    /// Existing keys are overwritten. If a key appears more than once in `entries`, the last
+    /// value is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an object. Use [`try_object_insert_many`] to handle this case.
+    ///
+    /// [`try_object_insert_many`]: #method.try_object_insert_many
+    ///
+    /// # Example
+    /// ```
+    /// let mut object: jsonbb::Value = r#"{"b": 1, "z": 0}"#.parse().unwrap();
+    /// let (x, y) = (jsonbb::Value::from("x"), jsonbb::Value::from(2));
+    /// object.object_insert_many([("c", x.as_ref()), ("b", y.as_ref())]);
+    /// assert_eq!(object.to_string(), r#"{"b":2,"c":"x","z":0}"#);
+    /// ```
+    pub fn object_insert_many<'a>(
+        &mut self,
+        entries: impl IntoIterator<Item = (&'a str, ValueRef<'a>)>,
+    ) {
+        if let Err(e) = self.try_object_insert_many(entries) {
+            panic!("{e}");
+        }
+    }
+
+    /// Inserts multiple key-value pairs into a JSON object with a single rebuild.
+    ///
+    /// Returns an error without modifying the value if it is not an object, or if the result
+    /// would be too large to encode.
+    pub fn try_object_insert_many<'a>(
+        &mut self,
+        entries: impl IntoIterator<Item = (&'a str, ValueRef<'a>)>,
+    ) -> Result<(), Error> {
+        let object = self.as_object_or_err()?;
+        let entries: Vec<_> = entries.into_iter().collect();
+        let size = entries.iter().fold(self.capacity(), |size, (k, v)| {
+            size + (4 + k.len() + 4) + (v.capacity() + 4)
+        });
+        check_size(size)?;
+        let new = Self::from_builder(size, |b| {
+            b.begin_object();
+            for (k, v) in object.iter() {
+                b.add_string(k);
+                b.add_value(v);
+            }
+            for (k, v) in entries {
+                b.add_string(k);
+                b.add_value(v);
+            }
+            b.end_object();
+        });
+        *self = new;
+        Ok(())
+    }
+
+    /// Renames a member of a JSON object, returning whether the key `from` existed.
+    ///
+    /// If `to` already exists, its value is overwritten by the renamed member, the same as
+    /// [`object_insert`]. Nothing changes if `from` doesn't exist.
+    ///
+    /// [`object_insert`]: #method.object_insert
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an object. Use [`try_rename_key`] to handle this case.
+    ///
+    /// [`try_rename_key`]: #method.try_rename_key
+    ///
+    /// # Example
+    /// ```
+    /// let mut object: jsonbb::Value = r#"{"a": 1, "b": 2, "z": 3}"#.parse().unwrap();
+    /// assert!(object.rename_key("a", "c"));
+    /// assert_eq!(object.to_string(), r#"{"b":2,"c":1,"z":3}"#);
+    ///
+    /// // an existing key is overwritten
+    /// assert!(object.rename_key("z", "b"));
+    /// assert_eq!(object.to_string(), r#"{"b":3,"c":1}"#);
+    ///
+    /// assert!(!object.rename_key("x", "y"));
+    /// ```
+    pub fn rename_key(&mut self, from: &str, to: &str) -> bool {
+        match self.try_rename_key(from, to) {
+            Ok(renamed) => renamed,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Renames a member of a JSON object, returning whether the key `from` existed.
+    ///
+    /// Returns an error without modifying the value if it is not an object, or if the result
+    /// would be too large to encode.
+    ///
+    /// # Example
+    /// ```
+    /// use jsonbb::{Error, JsonType, Value};
+    ///
+    /// let err = Value::from(1).try_rename_key("a", "b").unwrap_err();
+    /// assert!(matches!(err, Error::InvalidType { expected: JsonType::Object, found: JsonType::Number }));
+    /// ```
+    pub fn try_rename_key(&mut self, from: &str, to: &str) -> Result<bool, Error> {
+        let object = self.as_object_or_err()?;
+        if object.get(from).is_none() {
+            return Ok(false);
+        }
+        if from == to {
+            return Ok(true);
+        }
+        let size = self.capacity() + to.len();
+        check_size(size)?;
+        let new = Self::from_builder(size, |b| {
+            b.begin_object();
+            for (k, v) in object.iter() {
+                if k == to {
+                    continue;
+                }
+                b.add_string(if k == from { to } else { k });
+                b.add_value(v);
+            }
+            b.end_object();
+        });
+        *self = new;
+        Ok(true)
+    }
+
+    /// Sorts the elements of a JSON array.
+    ///
+    /// The sort is stable and uses the ordering of [`Value`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an array.
+    ///
+    /// # Example
+    /// ```
+    /// let mut array: jsonbb::Value = r#"[3, "a", null, 1, [], 2]"#.parse().unwrap();
+    /// array.sort_array();
+    /// assert_eq!(array.to_string(), r#"[null,"a",1,2,3,[]]"#);
+    /// ```
+    pub fn sort_array(&mut self) {
+        self.sort_array_with(|elems| elems.sort());
+    }
+
+    /// Sorts the elements of a JSON array by the value of the given key in each element.
+    ///
+    /// The sort is stable and uses the ordering of [`Value`]. Elements that are not objects or
+    /// don't have the key are placed first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an array.
+    ///
+    /// # Example
+    /// ```
+    /// let mut array: jsonbb::Value = r#"[{"id": 2}, {"id": 1}, {}]"#.parse().unwrap();
+    /// array.sort_array_by_key("id");
+    /// assert_eq!(array.to_string(), r#"[{},{"id":1},{"id":2}]"#);
+    /// ```
+    pub fn sort_array_by_key(&mut self, key: &str) {
+        self.sort_array_with(|elems| elems.sort_by_key(|v| v.get(key)));
+    }
+
+    /// Sorts the elements of a JSON array with the given function and rebuilds the buffer.
+    fn sort_array_with(&mut self, sort: impl FnOnce(&mut [ValueRef<'_>])) {
+        let array = self.as_array_or_err().unwrap_or_else(|e| panic!("{e}"));
+        let mut elems: Vec<_> = array.iter().collect();
+        sort(&mut elems);
+        *self = Self::from_builder(self.capacity(), |b| {
+            b.begin_array();
+            for v in elems {
+                b.add_value(v);
+            }
+            b.end_array();
+        });
+    }
+
+    fn as_array_or_err(&self) -> Result<ArrayRef<'_>, Error> {
+        self.as_array().ok_or_else(|| Error::InvalidType {
+            expected: JsonType::Array,
+            found: self.json_type(),
+        })
+    }
+
+    fn as_object_or_err(&self) -> Result<ObjectRef<'_>, Error> {
+        self.as_object().ok_or_else(|| Error::InvalidType {
+            expected: JsonType::Object,
+            found: self.json_type(),
+        })
+    }
+
+    fn from_builder(capacity: usize, f: impl FnOnce(&mut Builder)) -> Self {
+        let mut builder = Builder::with_capacity(capacity);
+        f(&mut builder);
+        builder.finish()
+    }
+}
+
+/// Options for parsing JSON text.
+///
+/// See [`Value::from_text_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+impl ParseOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how to handle duplicate keys in an object. Defaults to
+    /// [`DuplicateKeyPolicy::LastWins`].
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+}
+
+/// Limits for [`Value::prune`].
+///
+/// All limits default to unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneLimits {
+    max_depth: usize,
+    max_array_items: usize,
+    max_object_members: usize,
+    max_string_len: usize,
+}
+
+impl Default for PruneLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            max_array_items: usize::MAX,
+            max_object_members: usize::MAX,
+            max_string_len: usize::MAX,
+        }
+    }
+}
+
+impl PruneLimits {
+    /// The string that replaces arrays and objects nested too deep.
+    pub const TRUNCATED: &'static str = "<truncated>";
+
+    /// Creates limits that keep everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of nested arrays and objects.
+    ///
+    /// With a depth of 0, a top-level array or object is replaced as a whole.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the maximum number of elements kept in each array.
+    pub fn max_array_items(mut self, n: usize) -> Self {
+        self.max_array_items = n;
+        self
+    }
+
+    /// Sets the maximum number of members kept in each object, in key order.
+    pub fn max_object_members(mut self, n: usize) -> Self {
+        self.max_object_members = n;
+        self
+    }
+
+    /// Sets the maximum number of characters kept in each string value.
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    /// Returns true if any value of `size` bytes at `depth` is within the limits.
+    ///
+    /// A string can't have more characters than bytes, and each element of an array or object
+    /// takes at least a 4-byte entry, and each level of nesting at least 8 bytes.
+    fn contains_any(&self, size: usize, depth: usize) -> bool {
+        size <= self.max_string_len
+            && size / 4 <= self.max_array_items.min(self.max_object_members)
+            && depth.saturating_add(size / 8) <= self.max_depth
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_ref().fmt(f)
+    }
+}
+
+/// Display a JSON value as a string.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_ref().fmt(f)
+    }
+}
+
+/// # Example
+///
+/// ```
+/// let a: jsonbb::Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+/// let b: jsonbb::Value = r#"{"b": 2, "a": 1.0}"#.parse().unwrap();
+/// assert_eq!(a, b);
+/// ```
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref().eq(&other.as_ref())
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare two JSON values.
+///
+/// The ordering is defined as follows:
 /// <https://www.postgresql.org/docs/current/datatype-json.html#JSON-INDEXING>
 ///
 /// # Example
@@ -506,6 +1442,12 @@ impl From<&serde_json::Value> for Value {
     }
 }
 
+/// Creates a number, preserving whether it is a `u64`, `i64` or `f64`.
+///
+/// # Panics
+///
+/// With the `arbitrary_precision` feature of `serde_json`, panics if the number can not be
+/// represented as a `u64`, `i64` or `f64`.
 impl From<serde_json::Number> for Value {
     fn from(value: serde_json::Number) -> Self {
         Self::from(&value)
@@ -518,6 +1460,34 @@ impl From<&serde_json::Number> for Value {
     }
 }
 
+/// Creates an object from a `serde_json` map without wrapping it into a `serde_json::Value`.
+///
+/// # Example
+///
+/// ```
+/// let map: serde_json::Map<String, serde_json::Value> =
+///     serde_json::from_str(r#"{"b": [1], "a": null}"#).unwrap();
+/// assert_eq!(jsonbb::Value::from(&map).to_string(), r#"{"a":null,"b":[1]}"#);
+/// ```
+impl From<&serde_json::Map<String, serde_json::Value>> for Value {
+    fn from(map: &serde_json::Map<String, serde_json::Value>) -> Self {
+        Self::from_builder(0, |b| {
+            b.begin_object();
+            for (k, v) in map {
+                b.add_string(k);
+                b.add_serde_value(v);
+            }
+            b.end_object();
+        })
+    }
+}
+
+impl From<serde_json::Map<String, serde_json::Value>> for Value {
+    fn from(map: serde_json::Map<String, serde_json::Value>) -> Self {
+        Self::from(&map)
+    }
+}
+
 impl From<Value> for serde_json::Value {
     fn from(value: Value) -> Self {
         value.as_ref().into()
@@ -550,27 +1520,153 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         }
     }
 
-    /// Adds a serde `Number`.
-    fn add_serde_number(&mut self, n: &serde_json::Number) {
-        if let Some(i) = n.as_u64() {
-            self.add_u64(i)
-        } else if let Some(i) = n.as_i64() {
-            self.add_i64(i)
-        } else if let Some(f) = n.as_f64() {
-            self.add_f64(f)
-        } else {
-            panic!("invalid number");
+    /// Adds a `ValueRef` recursively to the builder, truncating strings longer than `max_len`.
+    fn add_value_with_truncated_strings(&mut self, value: ValueRef<'_>, max_len: usize) {
+        match value {
+            ValueRef::String(s) if s.len() > max_len => {
+                let mut end = max_len;
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                self.display(format_args!("{}…", &s[..end]));
+            }
+            ValueRef::Array(a) => {
+                self.begin_array();
+                for v in a.iter() {
+                    self.add_value_with_truncated_strings(v, max_len);
+                }
+                self.end_array();
+            }
+            ValueRef::Object(o) => {
+                self.begin_object();
+                for (k, v) in o.iter() {
+                    self.add_string(k);
+                    self.add_value_with_truncated_strings(v, max_len);
+                }
+                self.end_object();
+            }
+            _ => self.add_value(value),
         }
     }
-}
-
-impl FromStr for Value {
-    type Err = serde_json::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::from_text(s.as_bytes())
-    }
-}
+    /// Adds a `ValueRef` recursively to the builder, clamping it to the limits.
+    ///
+    /// `depth` is the number of arrays and objects enclosing the value.
+    fn add_value_with_limits(&mut self, value: ValueRef<'_>, limits: &PruneLimits, depth: usize) {
+        if limits.contains_any(value.capacity(), depth) {
+            return self.add_value(value);
+        }
+        match value {
+            ValueRef::String(s) => match s.char_indices().nth(limits.max_string_len) {
+                Some((end, _)) => self.display(format_args!("{}…", &s[..end])),
+                None => self.add_string(s),
+            },
+            ValueRef::Array(_) | ValueRef::Object(_) if depth >= limits.max_depth => {
+                self.add_string(PruneLimits::TRUNCATED)
+            }
+            ValueRef::Array(a) => {
+                self.begin_array();
+                for v in a.iter().take(limits.max_array_items) {
+                    self.add_value_with_limits(v, limits, depth + 1);
+                }
+                self.end_array();
+            }
+            ValueRef::Object(o) => {
+                self.begin_object();
+                for (k, v) in o.iter().take(limits.max_object_members) {
+                    self.add_string(k);
+                    self.add_value_with_limits(v, limits, depth + 1);
+                }
+                self.end_object();
+            }
+            _ => self.add_value(value),
+        }
+    }
+
+    /// Adds a `ValueRef` recursively to the builder, redacting the values of matching keys.
+    fn add_value_with_redacted_keys(
+        &mut self,
+        value: ValueRef<'_>,
+        should_redact: &impl Fn(&str) -> bool,
+    ) {
+        match value {
+            ValueRef::Array(a) => {
+                self.begin_array();
+                for v in a.iter() {
+                    self.add_value_with_redacted_keys(v, should_redact);
+                }
+                self.end_array();
+            }
+            ValueRef::Object(o) => {
+                self.begin_object();
+                for (k, v) in o.iter() {
+                    self.add_string(k);
+                    if should_redact(k) {
+                        self.add_string("***");
+                    } else {
+                        self.add_value_with_redacted_keys(v, should_redact);
+                    }
+                }
+                self.end_object();
+            }
+            _ => self.add_value(value),
+        }
+    }
+
+    /// Adds a `ValueRef` recursively to the builder, converting integral floats to integers.
+    fn add_value_with_normalized_numbers(&mut self, value: ValueRef<'_>) {
+        match value {
+            ValueRef::Number(n) if n.is_f64() => {
+                let f = n.as_f64().unwrap();
+                // the bounds are exactly 2^63 and 2^64
+                if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 {
+                    self.add_i64(f as i64);
+                } else if f.fract() == 0.0 && f >= 0.0 && f < u64::MAX as f64 {
+                    self.add_u64(f as u64);
+                } else {
+                    self.add_value(value);
+                }
+            }
+            ValueRef::Array(a) => {
+                self.begin_array();
+                for v in a.iter() {
+                    self.add_value_with_normalized_numbers(v);
+                }
+                self.end_array();
+            }
+            ValueRef::Object(o) => {
+                self.begin_object();
+                for (k, v) in o.iter() {
+                    self.add_string(k);
+                    self.add_value_with_normalized_numbers(v);
+                }
+                self.end_object();
+            }
+            _ => self.add_value(value),
+        }
+    }
+
+    /// Adds a serde `Number`.
+    fn add_serde_number(&mut self, n: &serde_json::Number) {
+        if let Some(i) = n.as_u64() {
+            self.add_u64(i)
+        } else if let Some(i) = n.as_i64() {
+            self.add_i64(i)
+        } else if let Some(f) = n.as_f64() {
+            self.add_f64(f)
+        } else {
+            panic!("invalid number");
+        }
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_text(s.as_bytes())
+    }
+}
 
 impl From<()> for Value {
     fn from(_: ()) -> Self {
@@ -608,6 +1704,9 @@ impl From<u64> for Value {
     }
 }
 
+// `usize` and `isize` are at most 64 bits on all supported targets, so the casts are lossless.
+const _: () = assert!(usize::BITS <= u64::BITS);
+
 impl From<usize> for Value {
     fn from(v: usize) -> Self {
         Self::from(v as u64)
@@ -640,10 +1739,123 @@ impl From<i64> for Value {
 
 impl From<isize> for Value {
     fn from(v: isize) -> Self {
-        Self::from(v as u64)
+        Self::from(v as i64)
+    }
+}
+
+/// Creates a number from a 128-bit integer.
+///
+/// Numbers are stored as `i64` or `u64`, so this fails if the integer is out of the range
+/// `i64::MIN..=u64::MAX`.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::Value;
+///
+/// assert_eq!(Value::try_from(u64::MAX as u128).unwrap().to_string(), "18446744073709551615");
+/// let err = Value::try_from(1_u128 << 64).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "integer 18446744073709551616 is out of the range [-9223372036854775808, 18446744073709551615]"
+/// );
+/// ```
+impl TryFrom<u128> for Value {
+    type Error = OutOfRangeError;
+
+    fn try_from(v: u128) -> Result<Self, Self::Error> {
+        match u64::try_from(v) {
+            Ok(v) => Ok(Self::from(v)),
+            Err(_) => Err(OutOfRangeError::unsigned(v)),
+        }
+    }
+}
+
+/// Creates a number from a 128-bit integer.
+///
+/// Numbers are stored as `i64` or `u64`, so this fails if the integer is out of the range
+/// `i64::MIN..=u64::MAX`.
+impl TryFrom<i128> for Value {
+    type Error = OutOfRangeError;
+
+    fn try_from(v: i128) -> Result<Self, Self::Error> {
+        if let Ok(v) = i64::try_from(v) {
+            Ok(Self::from(v))
+        } else if let Ok(v) = u64::try_from(v) {
+            Ok(Self::from(v))
+        } else {
+            Err(OutOfRangeError::signed(v))
+        }
+    }
+}
+
+/// An error returned when converting an integer that is out of the range `i64::MIN..=u64::MAX`
+/// into a [`Value`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfRangeError(Int128);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Int128 {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Int128::Signed(v) => write!(f, "integer {v}")?,
+            Int128::Unsigned(v) => write!(f, "integer {v}")?,
+        }
+        write!(f, " is out of the range [{}, {}]", i64::MIN, u64::MAX)
+    }
+}
+
+impl std::error::Error for OutOfRangeError {}
+
+impl OutOfRangeError {
+    pub(crate) fn signed(v: i128) -> Self {
+        Self(Int128::Signed(v))
+    }
+
+    pub(crate) fn unsigned(v: u128) -> Self {
+        Self(Int128::Unsigned(v))
     }
 }
 
+/// Implements `From<NonZero*>` and `TryFrom<NonZero*>` by delegating to the underlying integer.
+macro_rules! impl_from_nonzero {
+    ($($nonzero:ty),*) => {$(
+        impl From<$nonzero> for Value {
+            fn from(v: $nonzero) -> Self {
+                Self::from(v.get())
+            }
+        }
+    )*};
+    (try $($nonzero:ty),*) => {$(
+        impl TryFrom<$nonzero> for Value {
+            type Error = OutOfRangeError;
+
+            fn try_from(v: $nonzero) -> Result<Self, Self::Error> {
+                Self::try_from(v.get())
+            }
+        }
+    )*};
+}
+
+impl_from_nonzero!(
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroUsize,
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroIsize
+);
+impl_from_nonzero!(try std::num::NonZeroU128, std::num::NonZeroI128);
+
 impl From<f32> for Value {
     fn from(v: f32) -> Self {
         Self::from(v as f64)
@@ -662,8 +1874,85 @@ impl From<&str> for Value {
     }
 }
 
+/// Creates a string value.
+///
+/// # Example
+///
+/// ```
+/// let value = jsonbb::Value::from(String::from("hello"));
+/// assert_eq!(value.as_str(), Some("hello"));
+/// ```
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+/// Creates a string value.
+///
+/// # Example
+///
+/// ```
+/// let s = String::from("hello");
+/// assert_eq!(jsonbb::Value::from(&s).as_str(), Some("hello"));
+/// ```
+impl From<&String> for Value {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+/// Creates a string value.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let value = jsonbb::Value::from(Cow::Borrowed("hello"));
+/// assert_eq!(value.as_str(), Some("hello"));
+/// ```
+impl From<std::borrow::Cow<'_, str>> for Value {
+    fn from(s: std::borrow::Cow<'_, str>) -> Self {
+        Self::from(s.as_ref())
+    }
+}
+
+/// Creates a string value containing one character.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(jsonbb::Value::from('é').as_str(), Some("é"));
+/// ```
+impl From<char> for Value {
+    fn from(c: char) -> Self {
+        Self::from(&*c.encode_utf8(&mut [0; 4]))
+    }
+}
+
+/// Creates a value from an `Option`, mapping `None` to null.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(jsonbb::Value::from(Some(1)).to_string(), "1");
+/// assert_eq!(jsonbb::Value::from(None::<i32>).to_string(), "null");
+/// ```
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        match v {
+            Some(v) => v.into(),
+            None => Self::null(),
+        }
+    }
+}
+
 /// Creates a `Value` from bytes of jsonbb encoding.
 ///
+/// The bytes are not checked, the same as [`Value::from_bytes`]. Only use this for trusted
+/// bytes, and [`Value::try_from_bytes`] otherwise.
+///
 /// If you want to create a `Value` from JSON text, use [`FromStr`] or [`from_text`] instead.
 ///
 /// [`from_text`]: #method.from_text
@@ -680,42 +1969,1945 @@ impl From<ValueRef<'_>> for Value {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// A type that can be added to a [`Builder`] as a JSON value.
+///
+/// This is the element bound of the collection conversions such as `From<Vec<T>>`, which write
+/// all elements into a single buffer instead of creating a `Value` for each of them.
+///
+/// It is not implemented for `u8`, so that a `Vec<u8>` is not mistaken for the bytes of an
+/// encoded value. Use [`Value::from_bytes`] for those.
+///
+/// ```compile_fail
+/// let bytes: Vec<u8> = jsonbb::Value::from(1).as_bytes().to_vec();
+/// let value = jsonbb::Value::from(bytes);
+/// ```
+pub trait IntoValue {
+    /// Adds the value to the builder.
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>);
+}
 
-    #[test]
-    fn from_serde() {
-        let serde_value: serde_json::Value = r#"
-        {
-            "name": "John Doe",
-            "age": 43,
-            "phones": [
-                "+44 1234567",
-                "+44 2345678"
-            ]
-        }"#
-        .parse()
-        .unwrap();
-        let _value = Value::from(&serde_value);
+macro_rules! impl_into_value {
+    ($method:ident as $as:ty: $($ty:ty),*) => {
+        $(
+            impl IntoValue for $ty {
+                fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+                    builder.$method(self as $as);
+                }
+            }
+        )*
+    };
+}
+impl_into_value!(add_u64 as u64: u16, u32, u64, usize);
+impl_into_value!(add_i64 as i64: i8, i16, i32, i64, isize);
+impl_into_value!(add_f64 as f64: f32, f64);
+impl_into_value!(add_bool as bool: bool);
+
+impl IntoValue for () {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_null();
     }
+}
 
-    #[test]
-    #[should_panic]
-    fn from_nan() {
-        _ = Value::from(f64::NAN);
+impl IntoValue for &str {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_string(self);
     }
+}
 
-    #[test]
-    #[should_panic]
-    fn from_inf() {
-        _ = Value::from(f64::INFINITY);
+impl IntoValue for String {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_string(&self);
     }
+}
 
-    #[test]
-    #[should_panic]
-    fn from_neg_inf() {
-        _ = Value::from(f64::NEG_INFINITY);
+impl IntoValue for &String {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_string(self);
+    }
+}
+
+impl IntoValue for std::borrow::Cow<'_, str> {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_string(&self);
+    }
+}
+
+impl IntoValue for char {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_string(self.encode_utf8(&mut [0; 4]));
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        match self {
+            Some(v) => v.add_to(builder),
+            None => builder.add_null(),
+        }
+    }
+}
+
+impl IntoValue for ValueRef<'_> {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_value(self);
+    }
+}
+
+impl IntoValue for &Value {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_value(self.as_ref());
+    }
+}
+
+impl IntoValue for Value {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.add_value(self.as_ref());
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.begin_array();
+        for v in self {
+            v.add_to(builder);
+        }
+        builder.end_array();
+    }
+}
+
+impl<T: IntoValue, const N: usize> IntoValue for [T; N] {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.begin_array();
+        for v in self {
+            v.add_to(builder);
+        }
+        builder.end_array();
+    }
+}
+
+impl<T: IntoValue> IntoValue for std::collections::BTreeMap<String, T> {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.begin_object();
+        for (k, v) in self {
+            builder.add_string(&k);
+            v.add_to(builder);
+        }
+        builder.end_object();
+    }
+}
+
+impl<T: IntoValue, S> IntoValue for std::collections::HashMap<String, T, S> {
+    fn add_to<W: AsMut<Vec<u8>>>(self, builder: &mut Builder<W>) {
+        builder.begin_object();
+        for (k, v) in self {
+            builder.add_string(&k);
+            v.add_to(builder);
+        }
+        builder.end_object();
+    }
+}
+
+/// Creates an array from a vector.
+///
+/// Elements are written directly into a single buffer, without creating a `Value` for each
+/// of them.
+///
+/// # Example
+///
+/// ```
+/// let value = jsonbb::Value::from(vec![vec!["a"], vec![], vec!["b", "c"]]);
+/// assert_eq!(value.to_string(), r#"[["a"],[],["b","c"]]"#);
+/// ```
+impl<T: IntoValue> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Self {
+        Self::from_builder(v.len() * 8, |b| v.add_to(b))
+    }
+}
+
+/// Creates an array from a fixed-size array of values.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(jsonbb::Value::from([1, 2, 3]).to_string(), "[1,2,3]");
+/// ```
+impl<V: IntoValue, const N: usize> From<[V; N]> for Value {
+    fn from(array: [V; N]) -> Self {
+        Self::from_builder(N * 8, |b| array.add_to(b))
+    }
+}
+
+/// Creates an object from a fixed-size array of key-value pairs.
+///
+/// Keys are sorted as in any other object. If a key appears more than once, the last value wins.
+///
+/// # Example
+///
+/// ```
+/// let value = jsonbb::Value::from([("b", 1), ("a", 2), ("b", 3)]);
+/// assert_eq!(value.to_string(), r#"{"a":2,"b":3}"#);
+/// ```
+impl<V: IntoValue, const N: usize> From<[(&str, V); N]> for Value {
+    fn from(members: [(&str, V); N]) -> Self {
+        Self::from_builder(N * 16, |b| {
+            b.begin_object();
+            for (k, v) in members {
+                b.add_string(k);
+                v.add_to(b);
+            }
+            b.end_object();
+        })
+    }
+}
+
+/// Creates an object from a `BTreeMap`.
+impl<T: IntoValue> From<std::collections::BTreeMap<String, T>> for Value {
+    fn from(map: std::collections::BTreeMap<String, T>) -> Self {
+        Self::from_builder(map.len() * 16, |b| map.add_to(b))
+    }
+}
+
+/// Creates an object from a `HashMap`. Keys are sorted as in any other object.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// let map = HashMap::from([
+///     ("b".to_string(), vec![3_i64]),
+///     ("a".to_string(), vec![1, 2]),
+/// ]);
+/// let value = jsonbb::Value::from(map);
+/// assert_eq!(value.to_string(), r#"{"a":[1,2],"b":[3]}"#);
+/// ```
+impl<T: IntoValue, S> From<std::collections::HashMap<String, T, S>> for Value {
+    fn from(map: std::collections::HashMap<String, T, S>) -> Self {
+        Self::from_builder(map.len() * 16, |b| map.add_to(b))
+    }
+}
+
+/// Collects values into an array.
+///
+/// # Example
+///
+/// ```
+/// let value: jsonbb::Value = (1..=3).map(jsonbb::Value::from).collect();
+/// assert_eq!(value.to_string(), "[1,2,3]");
+/// ```
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        Self::from_builder(iter.size_hint().0 * 8, |b| {
+            b.begin_array();
+            for v in iter {
+                b.add_value(v.as_ref());
+            }
+            b.end_array();
+        })
+    }
+}
+
+/// Collects values into an array.
+impl<'a> FromIterator<ValueRef<'a>> for Value {
+    fn from_iter<I: IntoIterator<Item = ValueRef<'a>>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        Self::from_builder(iter.size_hint().0 * 8, |b| {
+            b.begin_array();
+            for v in iter {
+                b.add_value(v);
+            }
+            b.end_array();
+        })
+    }
+}
+
+/// Collects key-value pairs into an object.
+///
+/// Values are written directly into a single buffer. If a key appears more than once, the
+/// last value is used.
+///
+/// # Example
+///
+/// ```
+/// let value: jsonbb::Value = [("b", 1), ("a", 2), ("b", 3)].into_iter().collect();
+/// assert_eq!(value.to_string(), r#"{"a":2,"b":3}"#);
+/// ```
+///
+/// Values are bound by [`IntoValue`], so bytes are not mistaken for arrays of numbers:
+///
+/// ```compile_fail
+/// let value: jsonbb::Value = [("a", vec![1_u8])].into_iter().collect();
+/// ```
+impl<K: AsRef<str>, V: IntoValue> FromIterator<(K, V)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        Self::from_builder(iter.size_hint().0 * 16, |b| {
+            b.begin_object();
+            for (k, v) in iter {
+                b.add_string(k.as_ref());
+                v.add_to(b);
+            }
+            b.end_object();
+        })
+    }
+}
+
+/// Appends values to a JSON array.
+///
+/// The values are appended in place, the same as [`Value::array_push`], without rebuilding
+/// the existing elements.
+///
+/// # Panics
+///
+/// Panics if the value is not an array.
+///
+/// # Example
+///
+/// ```
+/// let mut array: jsonbb::Value = "[1]".parse().unwrap();
+/// let more: jsonbb::Value = "[2, 3]".parse().unwrap();
+/// array.extend(more.as_array().unwrap().iter());
+/// assert_eq!(array.to_string(), "[1,2,3]");
+/// ```
+impl<'a> Extend<ValueRef<'a>> for Value {
+    fn extend<T: IntoIterator<Item = ValueRef<'a>>>(&mut self, iter: T) {
+        if let Err(e) = self.try_array_append(iter) {
+            panic!("{e}");
+        }
+    }
+}
+
+/// Inserts key-value pairs into a JSON object.
+///
+/// This is the same as [`Value::object_insert_many`].
+///
+/// # Panics
+///
+/// Panics if the value is not an object.
+impl<'a> Extend<(&'a str, ValueRef<'a>)> for Value {
+    fn extend<T: IntoIterator<Item = (&'a str, ValueRef<'a>)>>(&mut self, iter: T) {
+        self.object_insert_many(iter);
+    }
+}
+
+/// Inserts key-value pairs into a JSON object.
+///
+/// The object is rebuilt and sorted once per call to `extend`. Existing keys are overwritten,
+/// and if a key appears more than once, the last value is used.
+///
+/// # Panics
+///
+/// Panics if the value is not an object.
+///
+/// # Example
+///
+/// ```
+/// let mut object: jsonbb::Value = r#"{"b": 1}"#.parse().unwrap();
+/// object.extend([("a".to_string(), jsonbb::Value::from(2))]);
+/// assert_eq!(object.to_string(), r#"{"a":2,"b":1}"#);
+/// ```
+impl Extend<(String, Value)> for Value {
+    fn extend<T: IntoIterator<Item = (String, Value)>>(&mut self, iter: T) {
+        let entries: Vec<_> = iter.into_iter().collect();
+        self.object_insert_many(entries.iter().map(|(k, v)| (k.as_str(), v.as_ref())));
+    }
+}
+
+/// Returns an error if a document of `size` bytes can not be encoded.
+///
+/// The entry at the end of a document points to the end of its payload, so this is the
+/// largest offset in the document.
+fn check_size(size: usize) -> Result<(), Error> {
+    if size.saturating_sub(4) > Entry::MAX_OFFSET {
+        return Err(Error::DocumentTooLarge { size });
+    }
+    Ok(())
+}
+
+/// Replaces comments and trailing commas outside of strings with whitespace.
+///
+/// Newlines inside block comments are kept so that error positions are unchanged. An
+/// unterminated block comment is left as is, for the parser to report.
+fn strip_comments_and_trailing_commas(json: &[u8]) -> Vec<u8> {
+    let mut out = json.to_vec();
+    // the position of the last comma after a value, if only whitespace has followed it
+    let mut comma = None;
+    // the last byte outside strings, comments and whitespace
+    let mut last = 0;
+    let mut i = 0;
+    while i < out.len() {
+        let c = out[i];
+        match c {
+            b'"' => {
+                comma = None;
+                i += 1;
+                while i < out.len() && out[i] != b'"' {
+                    i += if out[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'/' if out.get(i + 1) == Some(&b'/') => {
+                while i < out.len() && out[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if out.get(i + 1) == Some(&b'*') => {
+                let Some(len) = out[i + 2..].windows(2).position(|w| w == b"*/") else {
+                    break;
+                };
+                for b in &mut out[i..i + 2 + len + 2] {
+                    if !b.is_ascii_whitespace() {
+                        *b = b' ';
+                    }
+                }
+                i += 2 + len + 2;
+                continue;
+            }
+            _ if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b',' if !matches!(last, b'[' | b'{' | b',') => comma = Some(i),
+            b']' | b'}' => {
+                if let Some(comma) = comma.take() {
+                    out[comma] = b' ';
+                }
+            }
+            _ => comma = None,
+        }
+        last = c;
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(err: Error) -> serde_json::Error {
+        match err {
+            Error::Parse(e) => e,
+            e => panic!("not a parse error: {e}"),
+        }
+    }
+
+    #[test]
+    fn from_serde() {
+        let serde_value: serde_json::Value = r#"
+        {
+            "name": "John Doe",
+            "age": 43,
+            "phones": [
+                "+44 1234567",
+                "+44 2345678"
+            ]
+        }"#
+        .parse()
+        .unwrap();
+        let _value = Value::from(&serde_value);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_nan() {
+        _ = Value::from(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_inf() {
+        _ = Value::from(f64::INFINITY);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_neg_inf() {
+        _ = Value::from(f64::NEG_INFINITY);
+    }
+
+    #[test]
+    #[should_panic]
+    fn serialize_nan() {
+        _ = crate::to_value(vec![f64::NAN]);
+    }
+
+    #[test]
+    fn truncate_strings() {
+        let value: Value = r#"["abcdefgh", "abc", {"k": "abcdefgh"}, 1]"#.parse().unwrap();
+        assert_eq!(
+            value.truncate_strings(4).to_string(),
+            r#"["abcd…","abc",{"k":"abcd…"},1]"#
+        );
+
+        // "é" is 2 bytes in UTF-8, so a cut at 3 bytes falls mid-character.
+        let value = Value::from("éééé");
+        assert_eq!(value.truncate_strings(3).as_str(), Some("é…"));
+        assert_eq!(value.truncate_strings(8).as_str(), Some("éééé"));
+    }
+
+    #[test]
+    fn redact() {
+        let value: Value = r#"{
+            "user": {"name": "alice", "password": "p1", "token": {"id": 1}},
+            "sessions": [{"token": "t1", "ip": "::1"}, {"token": null}],
+            "password": ["p2"],
+            "note": "password"
+        }"#
+        .parse()
+        .unwrap();
+        let redacted = value.redact(|key| key == "password" || key == "token");
+        let expected: Value = r#"{
+            "user": {"name": "alice", "password": "***", "token": "***"},
+            "sessions": [{"token": "***", "ip": "::1"}, {"token": "***"}],
+            "password": "***",
+            "note": "password"
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(redacted, expected);
+
+        assert_eq!(value.redact(|_| false), value);
+        let value = Value::from("password");
+        assert_eq!(value.redact(|_| true), value);
+    }
+
+    #[test]
+    fn from_fixed_size_array() {
+        assert_eq!(Value::from([1, 2, 3]).to_string(), "[1,2,3]");
+        assert_eq!(Value::from([Some("a"), None]).to_string(), r#"["a",null]"#);
+        assert_eq!(
+            Value::from([Value::from([1]), Value::from([("x", true)])]).to_string(),
+            r#"[[1],{"x":true}]"#
+        );
+        assert_eq!(Value::from([0_i32; 0]).to_string(), "[]");
+        // elements are written in place, and accept the same types as `From<Vec<T>>`
+        assert_eq!(
+            Value::from([vec![1], vec![]]),
+            Value::from(vec![[1].to_vec(), vec![]])
+        );
+        assert_eq!(
+            Value::from([("a", [Some('x'), None])]).to_string(),
+            r#"{"a":["x",null]}"#
+        );
+
+        let value = Value::from([("b", 1.5), ("a", 2.0)]);
+        assert_eq!(value.to_string(), r#"{"a":2.0,"b":1.5}"#);
+        assert_eq!(Value::from([("a", ()); 0]).to_string(), "{}");
+
+        // duplicate keys, last wins
+        let value = Value::from([("a", 1), ("b", 2), ("a", 3)]);
+        assert_eq!(value, r#"{"a": 3, "b": 2}"#.parse::<Value>().unwrap());
+    }
+
+    #[test]
+    fn eq_fast_path() {
+        let parse = |s: &str| s.parse::<Value>().unwrap();
+        let cases = [
+            // same bytes
+            (
+                r#"[1, "a", {"b": [null]}]"#,
+                r#"[1, "a", {"b": [null]}]"#,
+                true,
+            ),
+            // different bytes, equal values
+            ("[1, 2]", "[1.0, 2]", true),
+            (r#"{"a": [1], "b": 2}"#, r#"{"a": [1.0], "b": 2.0}"#, true),
+            (r#"{"a": "x", "b": "y"}"#, r#"{"b": "y", "a": "x"}"#, true),
+            // different values
+            ("[1, 2]", "[1, 3]", false),
+            (r#"{"a": 1}"#, r#"{"b": 1}"#, false),
+        ];
+        for (i, (a, b, eq)) in cases.into_iter().enumerate() {
+            let (a, b) = (parse(a), parse(b));
+            // only the first case takes the fast path
+            assert_eq!(a.as_bytes() == b.as_bytes(), i == 0);
+            assert_eq!(a == b, eq, "{a} == {b}");
+            assert_eq!(b == a, eq, "{b} == {a}");
+            // the slow path compares element by element
+            let slow = match (a.as_ref(), b.as_ref()) {
+                (ValueRef::Array(a), ValueRef::Array(b)) => a.iter().eq(b.iter()),
+                (ValueRef::Object(a), ValueRef::Object(b)) => a.iter().eq(b.iter()),
+                _ => unreachable!(),
+            };
+            assert_eq!(slow, eq);
+        }
+    }
+
+    #[test]
+    fn extend() {
+        // mixed values, appended in place one call at a time
+        let mut array: Value = r#"[{"a": [1]}, "s"]"#.parse().unwrap();
+        let values: Value = r#"[null, true, 2.5, "x", [3, {}], {"b": null}]"#.parse().unwrap();
+        for v in values.as_array().unwrap().iter() {
+            array.extend([v]);
+        }
+        assert_eq!(
+            array.to_string(),
+            r#"[{"a":[1]},"s",null,true,2.5,"x",[3,{}],{"b":null}]"#
+        );
+        assert_eq!(Value::try_from_bytes(array.as_bytes()).unwrap(), array);
+
+        for n in [0, 1, 10, 100] {
+            let values: Vec<Value> = (0..n).map(Value::from).collect();
+
+            let mut array: Value = "[-1]".parse().unwrap();
+            array.extend(values.iter().map(|v| v.as_ref()));
+            let expected: Value = std::iter::once(Value::from(-1))
+                .chain(values.iter().cloned())
+                .collect();
+            assert_eq!(array, expected);
+
+            // keys are inserted in reverse order
+            let pairs: Vec<(String, Value)> = (0..n)
+                .rev()
+                .map(|i| (format!("k{i:03}"), Value::from(i)))
+                .collect();
+            let mut object: Value = r#"{"x": true}"#.parse().unwrap();
+            object.extend(pairs.iter().map(|(k, v)| (k.as_str(), v.as_ref())));
+            let expected: Value = pairs
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_ref()))
+                .chain([("x", ValueRef::Bool(true))])
+                .collect();
+            assert_eq!(object, expected);
+
+            let mut owned: Value = r#"{"x": true}"#.parse().unwrap();
+            owned.extend(pairs);
+            assert_eq!(owned, object);
+        }
+
+        // existing keys are overwritten, and the last duplicate wins
+        let mut object: Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+        object.extend([
+            ("b".to_string(), Value::from(3)),
+            ("b".to_string(), Value::from(4)),
+        ]);
+        assert_eq!(object.to_string(), r#"{"a":1,"b":4}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected array, found number")]
+    fn extend_not_array() {
+        let mut value = Value::from(1);
+        value.extend([ValueRef::Null]);
+    }
+
+    #[test]
+    fn from_integers() {
+        use std::num::{NonZeroI128, NonZeroI64, NonZeroIsize, NonZeroU128, NonZeroU64, NonZeroU8};
+
+        assert_eq!(Value::from(usize::MAX).as_u64(), Some(usize::MAX as u64));
+        assert_eq!(Value::from(isize::MIN).as_i64(), Some(isize::MIN as i64));
+        assert_eq!(Value::from(-1_isize).to_string(), "-1");
+
+        // 128-bit integers within `i64::MIN..=u64::MAX`
+        assert_eq!(Value::try_from(0_u128).unwrap(), Value::from(0));
+        assert_eq!(
+            Value::try_from(u64::MAX as u128).unwrap(),
+            Value::from(u64::MAX)
+        );
+        assert_eq!(
+            Value::try_from(i64::MIN as i128).unwrap(),
+            Value::from(i64::MIN)
+        );
+        assert_eq!(
+            Value::try_from(u64::MAX as i128).unwrap(),
+            Value::from(u64::MAX)
+        );
+
+        // and out of it
+        let range = "is out of the range [-9223372036854775808, 18446744073709551615]";
+        let err = Value::try_from(u64::MAX as u128 + 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("integer 18446744073709551616 {range}")
+        );
+        let err = Value::try_from(u64::MAX as i128 + 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("integer 18446744073709551616 {range}")
+        );
+        let err = Value::try_from(i64::MIN as i128 - 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("integer -9223372036854775809 {range}")
+        );
+        assert!(Value::try_from(u128::MAX).is_err());
+        assert!(Value::try_from(i128::MIN).is_err());
+
+        // non-zero integers
+        assert_eq!(Value::from(NonZeroU8::MAX), Value::from(u8::MAX));
+        assert_eq!(Value::from(NonZeroU64::MAX), Value::from(u64::MAX));
+        assert_eq!(Value::from(NonZeroI64::MIN), Value::from(i64::MIN));
+        assert_eq!(Value::from(NonZeroIsize::MIN), Value::from(isize::MIN));
+        assert_eq!(Value::try_from(NonZeroU128::MIN).unwrap(), Value::from(1));
+        assert!(Value::try_from(NonZeroU128::MAX).is_err());
+        assert!(Value::try_from(NonZeroI128::MIN).is_err());
+    }
+
+    #[test]
+    fn from_text_error_position() {
+        let err =
+            parse_error(Value::from_text(b"{\n  \"a\": [1, 2,],\n  \"b\": 3\n}").unwrap_err());
+        // the `]` after the trailing comma
+        assert_eq!((err.line(), err.column()), (2, 14));
+        assert!(err.is_syntax());
+
+        let err = parse_error(Value::from_text(b"[1,\n 2\n").unwrap_err());
+        assert_eq!((err.line(), err.column()), (3, 0));
+        assert!(err.is_eof());
+
+        let err = parse_error(Value::from_text(b"[1] x").unwrap_err());
+        assert_eq!((err.line(), err.column()), (1, 5));
+        assert_eq!(err.to_string(), "trailing characters at line 1 column 5");
+
+        // errors raised by the builder itself also carry the position, here the end of the object
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+        let err =
+            parse_error(Value::from_text_with(b"[\n{\"a\": 1, \"a\": 2}]", options).unwrap_err());
+        assert_eq!((err.line(), err.column()), (2, 16));
+        assert!(err.is_data());
+    }
+
+    #[test]
+    fn to_string_pretty() {
+        let value: Value = r#"{"a": [1, {"b": null}], "c": {}, "d": [], "e": "x"}"#
+            .parse()
+            .unwrap();
+        let expected = r#"{
+  "a": [
+    1,
+    {
+      "b": null
+    }
+  ],
+  "c": {},
+  "d": [],
+  "e": "x"
+}"#;
+        assert_eq!(value.to_string_pretty(), expected);
+        assert_eq!(value.to_vec_pretty(), expected.as_bytes());
+        assert_eq!(format!("{value:#}"), expected);
+        assert_eq!(
+            value.get("a").unwrap().to_string_pretty(),
+            "[\n  1,\n  {\n    \"b\": null\n  }\n]"
+        );
+
+        // scalars and empty containers are a single line, with no trailing newline
+        for json in ["null", "1.5", r#""s""#, "[]", "{}"] {
+            let value: Value = json.parse().unwrap();
+            assert_eq!(value.to_string_pretty(), json);
+        }
+
+        assert_eq!(value.to_vec(), value.to_string().into_bytes());
+        assert_eq!(
+            value.to_vec(),
+            br#"{"a":[1,{"b":null}],"c":{},"d":[],"e":"x"}"#
+        );
+    }
+
+    #[test]
+    fn entry() {
+        let tag = |s: &str| s.parse::<Value>().unwrap().as_ref().entry().tag();
+        assert_eq!(tag("null"), Entry::NULL_TAG);
+        assert_eq!(tag("false"), Entry::FALSE_TAG);
+        assert_eq!(tag("true"), Entry::TRUE_TAG);
+        assert_eq!(tag("1"), Entry::NUMBER_TAG);
+        assert_eq!(tag(r#""a""#), Entry::STRING_TAG);
+        assert_eq!(tag("[1]"), Entry::ARRAY_TAG);
+        assert_eq!(tag(r#"{"a": 1}"#), Entry::OBJECT_TAG);
+
+        // the entry of a top-level value is the last 4 bytes of its buffer
+        for json in [
+            "null",
+            "true",
+            "1.5",
+            r#""abc""#,
+            "[1, [2]]",
+            r#"{"a": {"b": []}}"#,
+        ] {
+            let value: Value = json.parse().unwrap();
+            let bytes = value.as_bytes();
+            let entry = value.as_ref().entry();
+            assert_eq!(entry.as_bytes(), &bytes[bytes.len() - 4..]);
+            assert_eq!(ValueRef::from_bytes(bytes), value.as_ref());
+        }
+
+        let value: Value = r#"{"a": [1, 2]}"#.parse().unwrap();
+        let entry = value.get("a").unwrap().entry();
+        assert!(entry.is_array());
+        assert_eq!(entry.offset(), value.get("a").unwrap().capacity());
+    }
+
+    #[test]
+    fn float_display() {
+        // floats are formatted by `serde_json`, so the output is the same
+        for f in [
+            0.0,
+            -0.0,
+            1e30,
+            1e15,
+            1e16,
+            0.0001,
+            0.00001,
+            -2.5e-9,
+            0.1 + 0.2,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+        ] {
+            let expected = serde_json::Value::from(f).to_string();
+            let value = Value::from(f);
+            assert_eq!(value.to_string(), expected);
+            assert_eq!(value.as_ref().as_number().unwrap().to_string(), expected);
+            assert_eq!(format!("{value:#}"), expected);
+        }
+
+        // integral floats keep their fraction, so they are parsed back as floats
+        for (f, expected) in [(0.0, "0.0"), (-0.0, "-0.0"), (1.0, "1.0"), (-2.0, "-2.0")] {
+            let value = Value::from(f);
+            assert_eq!(value.to_string(), expected);
+            let parsed: Value = expected.parse().unwrap();
+            assert_eq!(parsed.as_f64(), Some(f));
+            assert_eq!(parsed.as_i64(), None);
+        }
+    }
+
+    #[test]
+    fn splice_array() {
+        let (x, y) = (Value::from("x"), Value::from(["y"]));
+        let splice = |range, replacement: &[&Value]| {
+            let mut array: Value = "[0, 1, 2, 3, 4]".parse().unwrap();
+            array.splice_array(range, replacement.iter().map(|v| v.as_ref()));
+            array.to_string()
+        };
+        // more elements
+        assert_eq!(splice(1..3, &[&x, &y, &x]), r#"[0,"x",["y"],"x",3,4]"#);
+        // fewer elements
+        assert_eq!(splice(1..4, &[&x]), r#"[0,"x",4]"#);
+        assert_eq!(splice(1..4, &[]), "[0,4]");
+        // equal number of elements
+        assert_eq!(splice(2..4, &[&y, &x]), r#"[0,1,["y"],"x",4]"#);
+        // empty range inserts
+        assert_eq!(splice(5..5, &[&x]), r#"[0,1,2,3,4,"x"]"#);
+        assert_eq!(splice(0..0, &[&y]), r#"[["y"],0,1,2,3,4]"#);
+        // whole range
+        assert_eq!(splice(0..5, &[]), "[]");
+
+        let mut array: Value = "[]".parse().unwrap();
+        array.splice_array(0..0, [x.as_ref()]);
+        assert_eq!(array.to_string(), r#"["x"]"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 6 out of range for array of length 5")]
+    fn splice_array_out_of_range() {
+        let mut array: Value = "[0, 1, 2, 3, 4]".parse().unwrap();
+        array.splice_array(4..6, []);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected array, found object")]
+    fn splice_array_not_array() {
+        let mut object: Value = "{}".parse().unwrap();
+        object.splice_array(0..0, []);
+    }
+
+    #[test]
+    fn try_splice_array() {
+        let mut object: Value = "{}".parse().unwrap();
+        let err = object.try_splice_array(0..0, []).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidType {
+                expected: JsonType::Array,
+                found: JsonType::Object
+            }
+        ));
+        assert_eq!(object.to_string(), "{}");
+
+        let mut array: Value = "[0, 1, 2]".parse().unwrap();
+        let err = array.try_splice_array(2..4, []).unwrap_err();
+        assert!(matches!(err, Error::OutOfBounds { index: 4, len: 3 }));
+        let (start, end) = (2, 1);
+        let err = array.try_splice_array(start..end, []).unwrap_err();
+        assert!(matches!(err, Error::OutOfBounds { index: 2, len: 1 }));
+        assert_eq!(array.to_string(), "[0,1,2]");
+    }
+
+    #[test]
+    fn debug() {
+        let debug = |s: &str| format!("{:?}", s.parse::<Value>().unwrap());
+        assert_eq!(debug("null"), "Null");
+        assert_eq!(debug("true"), "Bool(true)");
+        assert_eq!(debug("0"), "Number(zero: 0)");
+        assert_eq!(debug("1"), "Number(i8: 1)");
+        assert_eq!(debug("-1"), "Number(i8: -1)");
+        assert_eq!(debug("300"), "Number(i16: 300)");
+        assert_eq!(debug("70000"), "Number(i32: 70000)");
+        assert_eq!(debug("-5000000000"), "Number(i64: -5000000000)");
+        assert_eq!(
+            debug("18446744073709551615"),
+            "Number(u64: 18446744073709551615)"
+        );
+        assert_eq!(debug("1.0"), "Number(f64: 1.0)");
+        assert_eq!(debug(r#""1""#), r#"String("1")"#);
+        assert_eq!(debug("[]"), "Array []");
+        assert_eq!(debug("{}"), "Object {}");
+        assert_eq!(
+            debug(r#"{"a": 1, "b": "x", "c": [null, 2.5]}"#),
+            r#"Object {"a": Number(i8: 1), "b": String("x"), "c": Array [Null, Number(f64: 2.5)]}"#
+        );
+        assert_ne!(
+            format!("{:?}", Value::from("1")),
+            format!("{:?}", Value::from(1))
+        );
+
+        let value: Value = r#"{"a": [1, "x"], "b": {}}"#.parse().unwrap();
+        assert_eq!(
+            format!("{value:#?}"),
+            r#"Object {
+    "a": Array [
+        Number(i8: 1),
+        String("x"),
+    ],
+    "b": Object {},
+}"#
+        );
+        // `Display` is still JSON text
+        assert_eq!(value.to_string(), r#"{"a":[1,"x"],"b":{}}"#);
+    }
+
+    #[test]
+    fn from_text_lenient() {
+        let parse = |s: &str| Value::from_text_lenient(s.as_bytes()).map(|v| v.to_string());
+
+        // line comments
+        let json = "// header\n{\"a\": 1, // the answer\n\"b\": \"//not a comment\"}\n// eof";
+        assert_eq!(parse(json).unwrap(), r#"{"a":1,"b":"//not a comment"}"#);
+
+        // block comments
+        let json = r#"/* multi
+            line */ [1, /**/ 2 /* , 3 */, "/* kept */", "\"/*"]"#;
+        assert_eq!(parse(json).unwrap(), r#"[1,2,"/* kept */","\"/*"]"#);
+
+        // trailing commas
+        assert_eq!(parse("[1, 2,]").unwrap(), "[1,2]");
+        assert_eq!(
+            parse(r#"{"a": [1,], "b": {"c": 2,} ,}"#).unwrap(),
+            r#"{"a":[1],"b":{"c":2}}"#
+        );
+        assert_eq!(parse("[1, // one\n]").unwrap(), "[1]");
+        assert_eq!(parse("[1, /* one */ ]").unwrap(), "[1]");
+        assert_eq!(parse(r#"[",]"]"#).unwrap(), r#"[",]"]"#);
+
+        // still invalid
+        assert!(parse("[,]").is_err());
+        assert!(parse("[1,,]").is_err());
+        assert!(parse("[1] /* unterminated").is_err());
+        assert!(parse("{a: 1}").is_err());
+        assert!(parse("['a']").is_err());
+
+        // error positions are unchanged
+        let err = parse_error(Value::from_text_lenient(b"/* a\nb */ [1,\n  x]").unwrap_err());
+        assert_eq!((err.line(), err.column()), (3, 3));
+    }
+
+    #[test]
+    fn try_mutators() {
+        let one = Value::from(1);
+        for (json, found) in [
+            ("null", JsonType::Null),
+            ("true", JsonType::Boolean),
+            ("1", JsonType::Number),
+            (r#""a""#, JsonType::String),
+            ("[]", JsonType::Array),
+            ("{}", JsonType::Object),
+        ] {
+            let mut value: Value = json.parse().unwrap();
+            let expect = |err: Error, expected: JsonType| {
+                assert!(
+                    matches!(err, Error::InvalidType { expected: e, found: f } if e == expected && f == found),
+                    "{err}"
+                );
+            };
+            if found != JsonType::Array {
+                expect(
+                    value.try_array_push(one.as_ref()).unwrap_err(),
+                    JsonType::Array,
+                );
+                expect(value.try_array_pop().unwrap_err(), JsonType::Array);
+            }
+            if found != JsonType::Object {
+                expect(
+                    value.try_object_insert("a", one.as_ref()).unwrap_err(),
+                    JsonType::Object,
+                );
+                expect(
+                    value
+                        .try_object_insert_many([("a", one.as_ref())])
+                        .unwrap_err(),
+                    JsonType::Object,
+                );
+            }
+            // the value is unchanged on error
+            assert_eq!(value, json.parse::<Value>().unwrap());
+        }
+
+        let mut array: Value = "[]".parse().unwrap();
+        array.try_array_push(one.as_ref()).unwrap();
+        assert_eq!(array.to_string(), "[1]");
+        assert_eq!(array.try_array_pop().unwrap(), Some(one.clone()));
+        assert_eq!(array.try_array_pop().unwrap(), None);
+
+        let mut object: Value = "{}".parse().unwrap();
+        object.try_object_insert("a", one.as_ref()).unwrap();
+        assert_eq!(object.to_string(), r#"{"a":1}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected array, found object")]
+    fn array_push_not_array() {
+        let mut object: Value = "{}".parse().unwrap();
+        object.array_push(ValueRef::Null);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected array, found string")]
+    fn array_pop_not_array() {
+        Value::from("a").array_pop();
+    }
+
+    #[test]
+    fn array_pop() {
+        let mut array: Value = r#"[1, {"a": [2]}, "x"]"#.parse().unwrap();
+        assert_eq!(array.array_pop().unwrap().to_string(), r#""x""#);
+        assert_eq!(array.to_string(), r#"[1,{"a":[2]}]"#);
+        assert_eq!(array.array_pop().unwrap().to_string(), r#"{"a":[2]}"#);
+        assert_eq!(array.array_pop().unwrap().to_string(), "1");
+        assert_eq!(array.array_pop(), None);
+        assert_eq!(array.to_string(), "[]");
+    }
+
+    #[test]
+    fn check_size() {
+        assert!(super::check_size(Entry::MAX_OFFSET + 4).is_ok());
+        let err = super::check_size(Entry::MAX_OFFSET + 5).unwrap_err();
+        assert!(matches!(err, Error::DocumentTooLarge { size } if size == Entry::MAX_OFFSET + 5));
+    }
+
+    #[test]
+    fn eq_ignore_extra_keys() {
+        let matches = |pattern: &str, value: &str| {
+            let pattern: Value = pattern.parse().unwrap();
+            let value: Value = value.parse().unwrap();
+            pattern.as_ref().eq_ignore_extra_keys(value.as_ref())
+        };
+        // subset patterns
+        assert!(matches(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#));
+        assert!(matches("{}", r#"{"a": 1}"#));
+        assert!(matches(
+            r#"{"user": {"name": "x"}, "tags": [{"k": 1}, {}]}"#,
+            r#"{"user": {"name": "x", "id": 7}, "tags": [{"k": 1, "v": 2}, {"k": 3}], "n": 0}"#
+        ));
+        assert!(matches("[1, 2.0]", "[1.0, 2]"));
+        assert!(matches("null", "null"));
+
+        // mismatching values
+        assert!(!matches(r#"{"a": 1, "b": 2}"#, r#"{"a": 1}"#));
+        assert!(!matches(r#"{"a": 1}"#, r#"{"a": 2, "b": 1}"#));
+        assert!(!matches(r#"{"a": {"b": 1}}"#, r#"{"a": {"c": 1}}"#));
+        assert!(!matches(r#"[{"a": 1}]"#, r#"[{"a": 1}, {"a": 2}]"#));
+        assert!(!matches("[1, 2]", "[2, 1]"));
+        assert!(!matches("{}", "[]"));
+        assert!(!matches(r#""1""#, "1"));
+    }
+
+    #[test]
+    fn prune() {
+        let value: Value = r#"{"a": [1, [2, [3]]], "s": "héllo", "o": {"x": 1, "y": 2}}"#
+            .parse()
+            .unwrap();
+        // within limits
+        assert_eq!(value.prune(&PruneLimits::new()), value);
+        assert_eq!(
+            value.prune(&PruneLimits::new().max_depth(4).max_string_len(5)),
+            value
+        );
+
+        let limits = PruneLimits::new().max_depth(2).max_string_len(2);
+        assert_eq!(
+            value.prune(&limits).to_string(),
+            r#"{"a":[1,"<truncated>"],"o":{"x":1,"y":2},"s":"hé…"}"#
+        );
+        let limits = PruneLimits::new().max_object_members(1).max_array_items(1);
+        assert_eq!(value.prune(&limits).to_string(), r#"{"a":[1]}"#);
+        assert_eq!(
+            value.prune(&PruneLimits::new().max_depth(0)).to_string(),
+            r#""<truncated>""#
+        );
+        // scalars are kept
+        assert_eq!(
+            Value::from(1).prune(&PruneLimits::new().max_depth(0)),
+            Value::from(1)
+        );
+    }
+
+    #[test]
+    fn prune_wide_and_deep() {
+        let deep: Value = format!("{}{}", "[".repeat(100), "]".repeat(100))
+            .parse()
+            .unwrap();
+        let pruned = deep.prune(&PruneLimits::new().max_depth(10));
+        assert_eq!(
+            pruned.to_string(),
+            format!(r#"{}"<truncated>"{}"#, "[".repeat(10), "]".repeat(10))
+        );
+        assert_eq!(pruned.to_string().parse::<Value>().unwrap(), pruned);
+
+        let long = "x".repeat(1000);
+        let wide: Value = format!(
+            "[{}]",
+            vec![format!(r#"{{"s": "{long}", "a": [{long:?}, [{long:?}]]}}"#); 1000].join(",")
+        )
+        .parse()
+        .unwrap();
+        let limits = PruneLimits::new()
+            .max_depth(3)
+            .max_array_items(4)
+            .max_object_members(4)
+            .max_string_len(8);
+        let pruned = wide.prune(&limits);
+        assert_eq!(pruned.as_array().unwrap().len(), 4);
+        assert_eq!(
+            pruned.get(0).unwrap().to_string(),
+            r#"{"a":["xxxxxxxx…","<truncated>"],"s":"xxxxxxxx…"}"#
+        );
+        assert!(pruned.capacity() < 1000);
+        assert_eq!(pruned.to_string().parse::<Value>().unwrap(), pruned);
+    }
+
+    #[test]
+    fn into_iter() {
+        struct Cursor<'a> {
+            iter: ObjectIter<'a>,
+        }
+
+        let value: Value = r#"{"b": [1, 2, 3], "a": null}"#.parse().unwrap();
+        let object = value.as_object().unwrap();
+        let mut cursor = Cursor {
+            iter: object.into_iter(),
+        };
+        assert_eq!(cursor.iter.len(), 2);
+        assert_eq!(cursor.iter.next_back().unwrap().0, "b");
+        assert_eq!(cursor.iter.next().unwrap().0, "a");
+        assert!(cursor.iter.next().is_none());
+
+        fn sum<'a>(iter: impl IntoIterator<Item = ValueRef<'a>>) -> u64 {
+            iter.into_iter().map(|v| v.as_u64().unwrap()).sum()
+        }
+        let array = value.get("b").unwrap().as_array().unwrap();
+        assert_eq!(sum(array), 6);
+        let mut count = 0;
+        for _ in &array {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        let reversed: Vec<_> = array.iter().rev().map(|v| v.to_string()).collect();
+        assert_eq!(reversed, ["3", "2", "1"]);
+        assert_eq!(
+            format!("{:?}", array.iter()),
+            "[Number(i8: 1), Number(i8: 2), Number(i8: 3)]"
+        );
+    }
+
+    #[test]
+    fn rename_key() {
+        let mut value: Value = r#"{"a": [1], "m": {"x": null}, "z": "s"}"#.parse().unwrap();
+        // to a new key, which sorts elsewhere
+        assert!(value.rename_key("z", "b"));
+        assert_eq!(value.to_string(), r#"{"a":[1],"b":"s","m":{"x":null}}"#);
+        assert_eq!(value.get("b").unwrap().as_str(), Some("s"));
+        assert!(value.get("z").is_none());
+
+        // to a colliding key
+        assert!(value.rename_key("a", "m"));
+        assert_eq!(value.to_string(), r#"{"b":"s","m":[1]}"#);
+
+        // to itself, and from a missing key
+        let before = value.clone();
+        assert!(value.rename_key("b", "b"));
+        assert!(!value.rename_key("a", "b"));
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected object, found number")]
+    fn rename_key_not_object() {
+        Value::from(1).rename_key("a", "b");
+    }
+
+    #[test]
+    fn get_ignore_ascii_case() {
+        let value: Value = r#"{"userid": 3, "userId": 2, "UserId": 1, "USERID": 0, "name": "x"}"#
+            .parse()
+            .unwrap();
+        // the first key in sorted order wins, even over an exact match
+        for key in ["userId", "USERID", "userid", "uSeRiD"] {
+            assert_eq!(value.get_ignore_ascii_case(key).unwrap().as_u64(), Some(0));
+        }
+        assert_eq!(
+            value.get_ignore_ascii_case("NAME").unwrap().as_str(),
+            Some("x")
+        );
+        assert!(value.get_ignore_ascii_case("user").is_none());
+        assert!(value.get_ignore_ascii_case("").is_none());
+        // non-ASCII letters are compared exactly
+        let value: Value = r#"{"Émile": 1}"#.parse().unwrap();
+        assert!(value.get_ignore_ascii_case("émile").is_none());
+        assert!(value.get_ignore_ascii_case("éMILE").is_none());
+        assert!(value.get_ignore_ascii_case("ÉMILE").is_some());
+        assert!(Value::from("userId")
+            .get_ignore_ascii_case("userId")
+            .is_none());
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        let value: Value = r#"{"a": [1, -1.5, "é", null, true], "b": {"c": {}}}"#
+            .parse()
+            .unwrap();
+        let bytes = value.as_bytes();
+        assert_eq!(Value::try_from_bytes(bytes).unwrap(), value);
+
+        let invalid = |bytes: &[u8]| match ValueRef::try_from_bytes(bytes) {
+            Err(Error::InvalidEncoding { reason }) => reason,
+            r => panic!("expected invalid encoding, got {r:?}"),
+        };
+        assert_eq!(invalid(&[]), "missing entry");
+        assert_eq!(
+            invalid(&[0xff, 0xff, 0xff, 0xbf]),
+            "container out of bounds"
+        );
+        assert_eq!(invalid(&[0, 0, 0, 0xe0]), "invalid entry tag");
+        assert_eq!(invalid(&bytes[4..]), "invalid container size");
+
+        // a string with invalid utf-8
+        let mut string = Value::from("é").as_bytes().to_vec();
+        string[4] = 0xff;
+        assert_eq!(invalid(&string), "invalid utf-8 in string");
+
+        // a NaN float
+        let mut float = Value::from(1.5).as_bytes().to_vec();
+        float[1..9].copy_from_slice(&f64::NAN.to_ne_bytes());
+        assert_eq!(invalid(&float), "non-finite number");
+
+        // an object whose key is a number
+        let mut object = Value::from([("a", 1)]).as_bytes().to_vec();
+        let n = object.len();
+        let key = n - 4 - 8 - 8;
+        object[key..key + 4].copy_from_slice(Value::from(1).as_ref().entry().as_bytes());
+        assert!(ValueRef::try_from_bytes(&object).is_err());
+
+        // an array whose only element is itself
+        let mut cyclic = vec![];
+        cyclic.extend_from_slice(Entry::array(12).as_bytes());
+        cyclic.extend_from_slice(&1u32.to_ne_bytes());
+        cyclic.extend_from_slice(&12u32.to_ne_bytes());
+        cyclic.extend_from_slice(Entry::array(12).as_bytes());
+        assert_eq!(invalid(&cyclic), "container out of bounds");
+
+        // a chain of arrays, each with two elements pointing at the previous array
+        let mut chain = vec![];
+        chain.extend_from_slice(&0u32.to_ne_bytes());
+        chain.extend_from_slice(&8u32.to_ne_bytes());
+        for _ in 0..64 {
+            let entry = Entry::array(chain.len());
+            chain.extend_from_slice(entry.as_bytes());
+            chain.extend_from_slice(entry.as_bytes());
+            chain.extend_from_slice(&2u32.to_ne_bytes());
+            chain.extend_from_slice(&(chain.len() as u32 + 4).to_ne_bytes());
+        }
+        chain.extend_from_slice(Entry::array(chain.len()).as_bytes());
+        assert_eq!(invalid(&chain), "overlapping values");
+    }
+
+    #[test]
+    fn try_get() {
+        let value: Value = r#"{"a": [1, 2]}"#.parse().unwrap();
+        assert_eq!(value.try_get("a").unwrap().unwrap().to_string(), "[1,2]");
+        assert!(value.try_get("x").unwrap().is_none());
+        let err = value.try_get(0).unwrap_err();
+        assert_eq!(err.to_string(), "expected array, found object");
+
+        let array = value.get("a").unwrap();
+        assert_eq!(array.try_get(1).unwrap().unwrap().as_u64(), Some(2));
+        assert!(array.try_get(2).unwrap().is_none());
+        assert_eq!(
+            array.try_get("a".to_string()).unwrap_err().to_string(),
+            "expected object, found array"
+        );
+        assert!(Value::null().try_get("a").is_err());
+    }
+
+    #[test]
+    fn from_text_prefix() {
+        let json = b"1 2 3";
+        let (value, len) = Value::from_text_prefix(json).unwrap();
+        assert_eq!(value, Value::from(1));
+        assert_eq!(len, 1);
+
+        let json = br#"  {"a": [1, 2]}{"b": null}"#;
+        let (value, len) = Value::from_text_prefix(json).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":[1,2]}"#);
+        assert_eq!(&json[len..], br#"{"b": null}"#);
+
+        Value::from_text_prefix(b"").unwrap_err();
+        Value::from_text_prefix(b"   ").unwrap_err();
+        Value::from_text_prefix(b"[1, 2").unwrap_err();
+    }
+
+    #[test]
+    fn clone_into_buffer() {
+        let values = [
+            r#"{"a": [1, 2], "b": "hello"}"#.parse::<Value>().unwrap(),
+            Value::from(()),
+            Value::from("a longer string than the others"),
+            Value::from(3.5),
+        ];
+        let mut buf = Vec::new();
+        for value in &values {
+            value.clone_into_buffer(&mut buf);
+            assert_eq!(buf, value.as_bytes());
+            assert_eq!(ValueRef::from_bytes(&buf), value.as_ref());
+            assert_eq!(&Value::from_bytes(&buf), value);
+        }
+    }
+
+    #[test]
+    fn try_from_f64() {
+        for v in [0.0, -0.0, 1.5, -2.25e10, f64::MAX, f64::MIN_POSITIVE] {
+            assert_eq!(Value::try_from_f64(v).unwrap(), Value::from(v));
+        }
+        for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let err = Value::try_from_f64(v).unwrap_err();
+            assert_eq!(err.value().to_bits(), v.to_bits());
+        }
+
+        // the builder is left untouched on error
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        builder.try_add_f64(f64::NAN).unwrap_err();
+        builder.try_add_f64(1.0).unwrap();
+        builder.end_array();
+        assert_eq!(builder.finish().to_string(), "[1.0]");
+    }
+
+    #[test]
+    fn sort_array() {
+        let mut value: Value = r#"[3, 1.5, -2, 1, 2.0, "b", "a", true, null, {"x": 1}, [0]]"#
+            .parse()
+            .unwrap();
+        value.sort_array();
+        assert_eq!(
+            value.to_string(),
+            r#"[null,"a","b",-2,1,1.5,2.0,3,true,[0],{"x":1}]"#
+        );
+        // elements are valid after the rebuild
+        let array = value.as_array().unwrap();
+        assert_eq!(array.len(), 11);
+        assert_eq!(array.get(9).unwrap().get(0).unwrap().as_u64(), Some(0));
+        assert_eq!(array.get(10).unwrap().get("x").unwrap().as_u64(), Some(1));
+    }
+
+    #[test]
+    fn sort_array_by_key() {
+        let mut value: Value = r#"[
+            {"name": "c", "age": 30},
+            {"name": "a", "age": 25},
+            {"name": "b", "age": 30, "tags": ["x", "y"]},
+            {"name": "d"}
+        ]"#
+        .parse()
+        .unwrap();
+        value.sort_array_by_key("age");
+        // stable: "c" stays before "b"
+        assert_eq!(
+            value.to_string(),
+            r#"[{"name":"d"},{"age":25,"name":"a"},{"age":30,"name":"c"},{"age":30,"name":"b","tags":["x","y"]}]"#
+        );
+        let tags = value.pointer("/3/tags").unwrap().as_array().unwrap();
+        assert_eq!(tags.as_vec_str().unwrap(), ["x", "y"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sort_non_array() {
+        Value::from(1).sort_array();
+    }
+
+    #[test]
+    fn get_many() {
+        let value: Value = r#"{"a": 1, "b": "x", "c": [1], "d": null, "e": {}}"#
+            .parse()
+            .unwrap();
+        let [e, zz, a, c] = value.get_many(["e", "zz", "a", "c"]);
+        assert_eq!(e.unwrap().to_string(), "{}");
+        assert!(zz.is_none());
+        assert_eq!(a.unwrap().as_u64(), Some(1));
+        assert_eq!(c.unwrap().to_string(), "[1]");
+
+        // duplicate and absent keys between present ones
+        let [b1, bb, b2, d] = value.get_many(["b", "bb", "b", "d"]);
+        assert_eq!(b1.unwrap().as_str(), Some("x"));
+        assert!(bb.is_none());
+        assert_eq!(b2.unwrap().as_str(), Some("x"));
+        assert_eq!(d.unwrap().as_null(), Some(()));
+
+        assert_eq!(Value::from(1).get_many(["a"]), [None]);
+        assert_eq!(value.get_many([]), []);
+    }
+
+    #[test]
+    fn object_get_path() {
+        let value: Value = r#"{"db": {"primary": {"host": "a", "port": 5432}}, "x": [{"y": 1}]}"#
+            .parse()
+            .unwrap();
+        let object = value.as_object().unwrap();
+        let port = object.get_path(&["db", "primary", "port"]).unwrap();
+        assert_eq!(port.as_u64(), Some(5432));
+        assert_eq!(
+            object.get_path(&["db", "primary"]).unwrap().to_string(),
+            r#"{"host":"a","port":5432}"#
+        );
+
+        // miss at the second level
+        assert!(object.get_path(&["db", "replica", "port"]).is_none());
+        // non-object intermediates
+        assert!(object.get_path(&["db", "primary", "host", "x"]).is_none());
+        assert!(object.get_path(&["x", "0", "y"]).is_none());
+
+        assert_eq!(object.get_path(&[]), Some(value.as_ref()));
+    }
+
+    #[test]
+    fn is_contained_in() {
+        let contained = |a: &str, b: &str| {
+            let a: Value = a.parse().unwrap();
+            let b: Value = b.parse().unwrap();
+            a.as_ref().is_contained_in(b.as_ref())
+        };
+
+        // objects
+        assert!(contained(r#"{}"#, r#"{"a": 1}"#));
+        assert!(contained(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#));
+        assert!(!contained(r#"{"a": 1, "b": 2}"#, r#"{"a": 1}"#));
+        assert!(!contained(r#"{"a": 1}"#, r#"{"a": 2}"#));
+        assert!(contained(r#"{"a": 1}"#, r#"{"a": 1.0}"#));
+
+        // nested objects
+        let doc = r#"{"user": {"name": "x", "roles": ["admin", "dev"], "meta": {"age": 3}}}"#;
+        assert!(contained(r#"{"user": {"meta": {}}}"#, doc));
+        assert!(contained(
+            r#"{"user": {"roles": ["dev"], "meta": {"age": 3}}}"#,
+            doc
+        ));
+        assert!(!contained(r#"{"user": {"meta": {"age": 4}}}"#, doc));
+        assert!(!contained(r#"{"user": {"name": ["x"]}}"#, doc));
+
+        // arrays
+        assert!(contained("[]", "[1]"));
+        assert!(contained("[2, 1, 1]", "[1, 2, 3]"));
+        assert!(!contained("[1, 4]", "[1, 2, 3]"));
+        assert!(contained("[[1, 3]]", "[[1, 2, 3], 4]"));
+        assert!(contained(r#"[{"a": 1}]"#, r#"[{"a": 1, "b": 2}]"#));
+        assert!(!contained("[1]", "[[1]]"));
+        assert!(!contained("[]", "{}"));
+
+        // scalars
+        assert!(contained("1", "1.0"));
+        assert!(contained(r#""a""#, r#""a""#));
+        assert!(!contained("null", "false"));
+        assert!(contained(r#""a""#, r#"["a", "b"]"#));
+        assert!(!contained(r#""a""#, r#"[["a"]]"#));
+        assert!(!contained("[1]", "1"));
+    }
+
+    #[test]
+    fn as_container_or_empty() {
+        let object: Value = r#"{"a": 1, "b": [2]}"#.parse().unwrap();
+        let members: Vec<_> = object
+            .as_object_or_empty()
+            .iter()
+            .map(|(k, v)| (k, v.to_string()))
+            .collect();
+        assert_eq!(members, [("a", "1".into()), ("b", "[2]".into())]);
+        assert_eq!(object.as_array_or_empty().iter().count(), 0);
+
+        for value in [Value::null(), Value::from(1), Value::from("a")] {
+            let o = value.as_object_or_empty();
+            assert_eq!(o.iter().count(), 0);
+            assert!(o.get("a").is_none());
+            assert_eq!(o.to_string(), "{}");
+            let a = value.as_array_or_empty();
+            assert_eq!(a.iter().count(), 0);
+            assert!(a.get(0).is_none());
+            assert_eq!(a.to_string(), "[]");
+        }
+
+        // the static empty containers equal parsed ones
+        assert_eq!(ArrayRef::empty(), Value::array([]).as_array().unwrap());
+        assert_eq!(ObjectRef::empty(), Value::object([]).as_object().unwrap());
+    }
+
+    #[test]
+    fn to_string_with_float_precision() {
+        let value: Value = r#"{"a": 3.14159, "b": [1, -2, 2.0, -0.125], "c": "3.14159", "d": 18446744073709551615}"#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value.to_string_with_float_precision(2),
+            r#"{"a":3.14,"b":[1,-2,2.00,-0.12],"c":"3.14159","d":18446744073709551615}"#
+        );
+        assert_eq!(
+            value.to_string_with_float_precision(0),
+            r#"{"a":3,"b":[1,-2,2,-0],"c":"3.14159","d":18446744073709551615}"#
+        );
+        assert_eq!(Value::from(7).to_string_with_float_precision(3), "7");
+    }
+
+    #[test]
+    fn count_nodes() {
+        assert_eq!(Value::from(1).count_nodes(), 1);
+        assert_eq!(Value::null().count_nodes(), 1);
+        assert_eq!(Value::array([]).count_nodes(), 1);
+        assert_eq!(Value::object([]).count_nodes(), 1);
+        let value: Value = r#"{
+            "a": [1, "two", null, [true, false]],
+            "b": {"c": {"d": {}}},
+            "e": 3.5
+        }"#
+        .parse()
+        .unwrap();
+        // root, a, 1, "two", null, [..], true, false, b, c, d, e
+        assert_eq!(value.count_nodes(), 12);
+    }
+
+    #[test]
+    fn object_insert_many() {
+        let mut value: Value = r#"{"b": 1, "d": [1, 2], "f": {"g": null}}"#.parse().unwrap();
+        let new = [
+            ("e", Value::from("e")),
+            ("a", Value::from(0)),
+            ("d", Value::from(true)),
+            ("c", Value::array([])),
+            ("g", Value::from(1.5)),
+        ];
+        value.object_insert_many(new.iter().map(|(k, v)| (*k, v.as_ref())));
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":0,"b":1,"c":[],"d":true,"e":"e","f":{"g":null},"g":1.5}"#
+        );
+
+        // the last duplicate wins
+        let (one, two) = (Value::from(1), Value::from(2));
+        value.object_insert_many([("x", one.as_ref()), ("x", two.as_ref())]);
+        assert_eq!(value.get("x").unwrap().as_u64(), Some(2));
+
+        value.object_insert("a", two.as_ref());
+        assert_eq!(value.get("a").unwrap().as_u64(), Some(2));
+        assert_eq!(value.as_object().unwrap().len(), 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn object_insert_non_object() {
+        Value::array([]).object_insert("a", Value::null().as_ref());
+    }
+
+    #[test]
+    fn cmp_values() {
+        let value: Value =
+            r#"[{}, [1], "b", false, [], 2, null, {"a": 1}, -1.5, true, "a", [0, 0]]"#
+                .parse()
+                .unwrap();
+        let mut values: Vec<_> = value.as_array().unwrap().iter().collect();
+        values.sort_by(|a, b| crate::cmp_values(*a, *b));
+        let sorted = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            sorted,
+            [
+                "null",
+                r#""a""#,
+                r#""b""#,
+                "-1.5",
+                "2",
+                "false",
+                "true",
+                "[]",
+                "[1]",
+                "[0,0]",
+                "{}",
+                r#"{"a":1}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn get_dotted() {
+        let value: Value = r#"{"a": {"b": [10, {"c": "x"}], "0": true}, "d.e": 1}"#
+            .parse()
+            .unwrap();
+        assert_eq!(value.get_dotted("a.b.0").unwrap().as_u64(), Some(10));
+        assert_eq!(value.get_dotted("a.b.1.c").unwrap().as_str(), Some("x"));
+        // numeric segment on an object is a key
+        assert_eq!(value.get_dotted("a.0").unwrap().as_bool(), Some(true));
+        assert_eq!(value.get_dotted(""), Some(value.as_ref()));
+        // misses
+        assert!(value.get_dotted("a.b.2").is_none());
+        assert!(value.get_dotted("a.b.x").is_none());
+        assert!(value.get_dotted("a.b.0.c").is_none());
+        assert!(value.get_dotted("a.").is_none());
+        // indices have no sign or leading zeros
+        assert!(value.get_dotted("a.b.+1").is_none());
+        assert!(value.get_dotted("a.b.01").is_none());
+        // keys containing dots can't be reached
+        assert!(value.get_dotted("d.e").is_none());
+    }
+
+    #[test]
+    fn json_macro() {
+        use crate::json;
+        use std::collections::HashMap;
+
+        let items = vec![json!({"x": 1}), json!([true])];
+        let fields = HashMap::from([("k".to_string(), vec![1, 2])]);
+        let (name, missing) = (Some("jsonbb"), None::<u32>);
+        let key = "dynamic";
+        let numbers = 0..2;
+        let value = json!({
+            "array": [null, true, false, [], {}, 1 + 1, ..&items, ..numbers,],
+            "name": ?name,
+            "missing": ?missing,
+            key: f64::NAN,
+            "nested": {"a": [{"b": -1.5}], ..fields},
+        });
+        assert_eq!(
+            value.to_string(),
+            r#"{"array":[null,true,false,[],{},2,{"x":1},[true],0,1],"dynamic":null,"name":"jsonbb","nested":{"a":[{"b":-1.5}],"k":[1,2]}}"#
+        );
+        assert_eq!(json!(null), Value::null());
+        assert_eq!(json!([]).to_string(), "[]");
+        assert_eq!(json!({}).to_string(), "{}");
+        assert_eq!(json!(items[1]).to_string(), "[true]");
+    }
+
+    #[test]
+    fn from_collections() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let value = Value::from(vec![1, 2, 3]);
+        let array = value.as_array().unwrap();
+        assert_eq!(array.as_vec_i64().unwrap(), [1, 2, 3]);
+
+        let value = Value::from(vec![vec!["a"], vec![], vec!["b", "c"]]);
+        assert_eq!(value.to_string(), r#"[["a"],[],["b","c"]]"#);
+
+        let value = Value::from(vec![Value::null(), Value::from("x")]);
+        assert_eq!(value.to_string(), r#"[null,"x"]"#);
+        assert_eq!(Value::from(Vec::<u16>::new()).to_string(), "[]");
+
+        let btree = BTreeMap::from([("y".to_string(), 1.5), ("x".to_string(), 2.0)]);
+        let value = Value::from(btree.clone());
+        let object = value.as_object().unwrap();
+        assert_eq!(object.len(), 2);
+        for (k, v) in object.iter() {
+            assert_eq!(v.as_f64(), btree.get(k).copied());
+        }
+
+        let hash: HashMap<String, bool> = (0..100).map(|i| (i.to_string(), i % 2 == 0)).collect();
+        let value = Value::from(hash.clone());
+        let object = value.as_object().unwrap();
+        assert_eq!(object.len(), 100);
+        assert!(object.keys().is_sorted());
+        for (k, v) in object.iter() {
+            assert_eq!(v.as_bool(), hash.get(k).copied());
+        }
+    }
+
+    #[test]
+    fn normalize_numbers() {
+        let mut value: Value =
+            r#"[2.0, -0.0, 0.5, -2.5, 1e300, 255.0, -129.0, 1e10, 1.5e19, 4e19, 3, {"x": [7.0]}]"#
+                .parse()
+                .unwrap();
+        let size = value.capacity();
+        value.normalize_numbers();
+        assert_eq!(
+            value.to_string(),
+            r#"[2,0,0.5,-2.5,1e+300,255,-129,10000000000,15000000000000000000,4e+19,3,{"x":[7]}]"#
+        );
+        assert!(value.capacity() < size);
+        let array = value.as_array().unwrap();
+        assert!(array.get(0).unwrap().as_number().unwrap().is_u64());
+        assert_eq!(
+            array.get(8).unwrap().as_u64(),
+            Some(15_000_000_000_000_000_000)
+        );
+
+        let mut value = Value::from("2.0");
+        value.normalize_numbers();
+        assert_eq!(value.as_str(), Some("2.0"));
+    }
+
+    #[test]
+    fn from_iterator() {
+        let value: Value = std::iter::empty::<Value>().collect();
+        assert_eq!(value.to_string(), "[]");
+        let value: Value = std::iter::empty::<ValueRef<'_>>().collect();
+        assert_eq!(value.to_string(), "[]");
+        let value: Value = std::iter::empty::<(&str, u32)>().collect();
+        assert_eq!(value.to_string(), "{}");
+
+        let values: Vec<Value> = ["1", "\"a\"", "[null]"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let from_refs: Value = values.iter().map(|v| v.as_ref()).collect();
+        let from_values: Value = values.into_iter().collect();
+        assert_eq!(from_refs.to_string(), r#"[1,"a",[null]]"#);
+        assert_eq!(from_refs, from_values);
+
+        // duplicate keys: the last wins
+        let value: Value = [
+            ("z".to_string(), Value::from(1)),
+            ("a".to_string(), Value::from("first")),
+            ("a".to_string(), Value::from("second")),
+            ("m".to_string(), Value::array([])),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(value.to_string(), r#"{"a":"second","m":[],"z":1}"#);
+    }
+
+    #[test]
+    fn serialize_to_smallvec() {
+        let bytes = Value::from(true).serialize_to_smallvec();
+        assert_eq!(&bytes[..], b"true");
+        assert!(!bytes.spilled());
+
+        let bytes = Value::from("hello\n").serialize_to_smallvec();
+        assert_eq!(&bytes[..], br#""hello\n""#);
+        assert!(!bytes.spilled());
+
+        // long outputs spill to the heap
+        let long = "x".repeat(100);
+        let bytes = Value::from(long.as_str()).serialize_to_smallvec();
+        assert_eq!(bytes.len(), 102);
+        assert!(bytes.spilled());
+    }
+
+    #[test]
+    fn from_option_and_strings() {
+        use crate::json;
+        use std::borrow::Cow;
+
+        assert_eq!(Value::from(Some("a")).as_str(), Some("a"));
+        assert_eq!(Value::from(None::<&str>), Value::null());
+        assert_eq!(Value::from(Some(Some(1.5))).as_f64(), Some(1.5));
+        assert_eq!(Value::from(String::new()).as_str(), Some(""));
+        assert_eq!(
+            Value::from(Cow::Owned::<str>("b".into())).as_str(),
+            Some("b")
+        );
+        assert_eq!(Value::from('🦀').as_str(), Some("🦀"));
+
+        let (some, none) = (Some(1), None::<i32>);
+        assert_eq!(
+            json!({"some": some, "none": none, "opt": ?some, "skip": ?none}).to_string(),
+            r#"{"none":null,"opt":1,"some":1}"#
+        );
+        assert_eq!(json!([some, none]), Value::from(vec![some, none]));
+    }
+
+    #[test]
+    fn duplicate_key_policy() {
+        let json = br#"{"a": 1, "b": {"x": [], "x": {}}, "a": 2}"#;
+        let parse =
+            |policy| Value::from_text_with(json, ParseOptions::new().duplicate_keys(policy));
+
+        let value = parse(DuplicateKeyPolicy::LastWins).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":2,"b":{"x":{}}}"#);
+        assert_eq!(value, Value::from_text(json).unwrap());
+
+        let value = parse(DuplicateKeyPolicy::FirstWins).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":1,"b":{"x":[]}}"#);
+
+        let err = parse(DuplicateKeyPolicy::Error).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "duplicate key `x` in object at line 1 column 32"
+        );
+
+        let value = Value::from_text_with(
+            br#"{"a": 1, "b": 2}"#,
+            ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error),
+        )
+        .unwrap();
+        assert_eq!(value.to_string(), r#"{"a":1,"b":2}"#);
+    }
+
+    #[test]
+    fn from_serde_number_and_map() {
+        let value = Value::from(serde_json::Number::from(u64::MAX));
+        assert_eq!(value.as_u64(), Some(u64::MAX));
+        assert_eq!(value.to_string(), "18446744073709551615");
+        let value = Value::from(serde_json::Number::from(i64::MIN));
+        assert_eq!(value.as_i64(), Some(i64::MIN));
+        let value = Value::from(serde_json::Number::from_f64(0.5).unwrap());
+        assert!(value.is_f64());
+        assert_eq!(value.as_f64(), Some(0.5));
+        let value = Value::from(serde_json::Number::from(1));
+        assert!(!value.is_f64());
+
+        let serde_value: serde_json::Value =
+            r#"{"max": 18446744073709551615, "nested": {"x": [-1, 2.5]}}"#
+                .parse()
+                .unwrap();
+        let map = serde_value.as_object().unwrap();
+        let value = Value::from(map);
+        assert_eq!(value, Value::from(&serde_value));
+        assert_eq!(value.get("max").unwrap().as_u64(), Some(u64::MAX));
+        assert_eq!(Value::from(map.clone()), value);
+        assert_eq!(Value::from(&serde_json::Map::new()).to_string(), "{}");
+    }
+
+    #[test]
+    fn default_null() {
+        #[derive(Default)]
+        struct Row {
+            value: Value,
+        }
+
+        let mut row = Row::default();
+        assert!(row.value.is_default());
+        row.value = Value::from(1);
+        assert!(!row.value.is_default());
+        let taken = std::mem::take(&mut row.value);
+        assert_eq!(taken.as_u64(), Some(1));
+        assert!(row.value.is_null());
+
+        let mut values = vec![Value::from("a")];
+        values.resize_with(3, Default::default);
+        assert_eq!(
+            Value::array(values.iter().map(|v| v.as_ref())).to_string(),
+            r#"["a",null,null]"#
+        );
+
+        assert_eq!(Value::null_static(), &Value::default());
+        assert!(std::ptr::eq(Value::null_static(), Value::null_static()));
+    }
+
+    #[test]
+    fn container_len() {
+        // the length is read from the footer: `len (u32) | size (u32)` at the end of the slice
+        fn footer_len(slice: &[u8]) -> usize {
+            u32::from_ne_bytes(slice[slice.len() - 8..slice.len() - 4].try_into().unwrap()) as usize
+        }
+
+        let mut builder = ArrayBuilder::with_capacity(1 << 20);
+        for i in 0..100_000u64 {
+            builder.push(Value::from(i).as_ref());
+        }
+        let array = builder.finish();
+        let array = array.as_array().unwrap();
+        assert_eq!(array.len(), 100_000);
+        assert_eq!(footer_len(array.as_slice()), 100_000);
+        assert!(!array.is_empty());
+
+        let object: Value = (0..1000).map(|i| (i.to_string(), i)).collect();
+        let object = object.as_object().unwrap();
+        assert_eq!(object.len(), 1000);
+        assert_eq!(footer_len(object.as_slice()), 1000);
+        assert!(!object.is_empty());
+
+        let empty = Value::array([]);
+        assert_eq!(empty.as_array().unwrap().len(), 0);
+        assert!(empty.as_array().unwrap().is_empty());
+        let empty = Value::object([]);
+        assert_eq!(empty.as_object().unwrap().len(), 0);
+        assert!(empty.as_object().unwrap().is_empty());
+        assert!(ArrayRef::empty().is_empty());
+        assert!(ObjectRef::empty().is_empty());
+    }
+
+    #[test]
+    fn write_to() {
+        let a: Value = r#"{"s": "line\nbreak", "n": [1.5, -2]}"#.parse().unwrap();
+        let b = Value::from("ünïcode");
+        let mut buf = vec![];
+        a.write_to(&mut buf);
+        b.as_ref().write_to(&mut buf);
+        assert_eq!(buf, format!("{a}{b}").into_bytes());
+    }
+
+    #[test]
+    fn number_cmp() {
+        use std::cmp::Ordering;
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(v: &Value) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        }
+        let n = |s: &str| s.parse::<Value>().unwrap();
+
+        // integers and floats with the same value are equal
+        for (a, b) in [
+            ("2", "2.0"),
+            ("0", "-0.0"),
+            ("-3", "-3.0"),
+            ("4096", "4.096e3"),
+        ] {
+            let (a, b) = (n(a), n(b));
+            assert_eq!(a.cmp(&b), Ordering::Equal);
+            assert_eq!(a, b);
+            assert_eq!(hash(&a), hash(&b));
+        }
+        assert_eq!(n("[1, 2.0]").cmp(&n("[1.0, 2]")), Ordering::Equal);
+
+        assert!(n("1") < n("1.5"));
+        assert!(n("-1.5") < n("-1"));
+        assert!(n("-0.5") < n("0"));
+        assert!(n("9223372036854775807") > n("-9223372036854775808"));
+
+        // integers are compared with floats exactly
+        let cmp = |a: Value, b: Value| a.cmp(&b);
+        let two_pow_64 = 18446744073709551616.0;
+        assert_eq!(
+            cmp(u64::MAX.into(), (u64::MAX - 1).into()),
+            Ordering::Greater
+        );
+        assert_eq!(cmp(u64::MAX.into(), two_pow_64.into()), Ordering::Less);
+        assert_eq!(
+            cmp((u64::MAX - 1).into(), two_pow_64.into()),
+            Ordering::Less
+        );
+        assert_ne!(Value::from(u64::MAX), Value::from(two_pow_64));
+        let min = -9223372036854775808.0;
+        assert_eq!(cmp(i64::MIN.into(), min.into()), Ordering::Equal);
+        assert_eq!(cmp((i64::MIN + 1).into(), min.into()), Ordering::Greater);
+        assert_eq!(cmp(i64::MIN.into(), (-1e19).into()), Ordering::Greater);
+        let two_pow_53 = 9007199254740992.0;
+        assert_eq!(cmp((1u64 << 53).into(), two_pow_53.into()), Ordering::Equal);
+        assert_eq!(
+            cmp(((1u64 << 53) + 1).into(), two_pow_53.into()),
+            Ordering::Greater
+        );
     }
 
     #[test]
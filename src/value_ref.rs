@@ -12,12 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
 
 use super::*;
 use bytes::Buf;
 use serde_json::Number;
+use smallvec::SmallVec;
 
 /// A reference to a JSON value.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -39,13 +42,81 @@ pub enum ValueRef<'a> {
     Object(ObjectRef<'a>),
 }
 
+/// The type of a JSON value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JsonType {
+    /// JSON null.
+    Null,
+    /// JSON boolean.
+    Boolean,
+    /// JSON number.
+    Number,
+    /// JSON string.
+    String,
+    /// JSON array.
+    Array,
+    /// JSON object.
+    Object,
+}
+
+impl JsonType {
+    /// Returns the name of the type, e.g. `"boolean"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl<'a> ValueRef<'a> {
     /// Creates a `ValueRef` from a byte slice.
+    ///
+    /// The bytes must be a valid encoding, such as the result of [`Value::as_bytes`]. They are
+    /// not checked, so invalid bytes may cause panics or wrong results when the value is read.
+    /// Use [`try_from_bytes`] for bytes from an untrusted source.
+    ///
+    /// [`try_from_bytes`]: #method.try_from_bytes
     pub fn from_bytes(bytes: &[u8]) -> ValueRef<'_> {
         let entry = Entry::from(&bytes[bytes.len() - 4..]);
         ValueRef::from_slice(bytes, entry)
     }
 
+    /// Creates a `ValueRef` from a byte slice, checking that it is a valid encoding.
+    ///
+    /// The whole document is checked once, in `O(n)`, so that reading the value afterwards
+    /// can't panic. Returns [`Error::InvalidEncoding`] if the bytes are not valid.
+    ///
+    /// # Examples
+    /// ```
+    /// let value: jsonbb::Value = r#"{"a": [1, "b"]}"#.parse().unwrap();
+    /// let bytes = value.as_bytes();
+    /// assert_eq!(jsonbb::ValueRef::try_from_bytes(bytes).unwrap(), value.as_ref());
+    ///
+    /// assert!(jsonbb::ValueRef::try_from_bytes(&bytes[1..]).is_err());
+    /// assert!(jsonbb::ValueRef::try_from_bytes(&[]).is_err());
+    /// ```
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<ValueRef<'_>, Error> {
+        if bytes.len() < 4 {
+            return Err(Error::InvalidEncoding {
+                reason: "missing entry",
+            });
+        }
+        let entry = Entry::from(&bytes[bytes.len() - 4..]);
+        validate(bytes, entry).map_err(|reason| Error::InvalidEncoding { reason })?;
+        Ok(ValueRef::from_slice(bytes, entry))
+    }
+
     /// Returns true if the value is a null. Returns false otherwise.
     pub fn is_null(self) -> bool {
         matches!(self, Self::Null)
@@ -91,6 +162,18 @@ impl<'a> ValueRef<'a> {
         matches!(self, Self::Object(_))
     }
 
+    /// Returns the type of the value.
+    pub fn json_type(self) -> JsonType {
+        match self {
+            Self::Null => JsonType::Null,
+            Self::Bool(_) => JsonType::Boolean,
+            Self::Number(_) => JsonType::Number,
+            Self::String(_) => JsonType::String,
+            Self::Array(_) => JsonType::Array,
+            Self::Object(_) => JsonType::Object,
+        }
+    }
+
     /// If the value is `null`, returns `()`. Returns `None` otherwise.
     pub fn as_null(self) -> Option<()> {
         match self {
@@ -147,6 +230,14 @@ impl<'a> ValueRef<'a> {
         }
     }
 
+    /// If the value is a base64-encoded string, returns the decoded bytes. Returns `None` otherwise.
+    #[cfg(feature = "base64")]
+    pub fn as_base64_bytes(self) -> Option<Vec<u8>> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        STANDARD.decode(self.as_str()?).ok()
+    }
+
     /// If the value is an array, returns the associated array. Returns `None` otherwise.
     pub fn as_array(self) -> Option<ArrayRef<'a>> {
         match self {
@@ -163,6 +254,34 @@ impl<'a> ValueRef<'a> {
         }
     }
 
+    /// If the value is an array, returns the associated array. Returns an empty array otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"[1, 2]"#.parse().unwrap();
+    /// assert_eq!(json.as_ref().as_array_or_empty().len(), 2);
+    ///
+    /// let json = jsonbb::Value::from(1);
+    /// assert_eq!(json.as_ref().as_array_or_empty().iter().count(), 0);
+    /// ```
+    pub fn as_array_or_empty(self) -> ArrayRef<'a> {
+        self.as_array().unwrap_or(ArrayRef::empty())
+    }
+
+    /// If the value is an object, returns the associated map. Returns an empty object otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": 1}"#.parse().unwrap();
+    /// assert_eq!(json.as_ref().as_object_or_empty().len(), 1);
+    ///
+    /// let json = jsonbb::Value::from("a");
+    /// assert_eq!(json.as_ref().as_object_or_empty().iter().count(), 0);
+    /// ```
+    pub fn as_object_or_empty(self) -> ObjectRef<'a> {
+        self.as_object().unwrap_or(ObjectRef::empty())
+    }
+
     /// Creates owned `Value` from `ValueRef`.
     pub fn to_owned(self) -> Value {
         self.into()
@@ -225,6 +344,26 @@ impl<'a> ValueRef<'a> {
         }
     }
 
+    /// Returns the entry of the value, as stored at the end of a top-level buffer.
+    ///
+    /// The offset of the entry is relative to the start of the value's own bytes, i.e. it is the
+    /// entry found in the last 4 bytes of `Value::as_bytes`. The entry of the same value nested
+    /// in an array or object has the same tag, but its offset is relative to the enclosing
+    /// container.
+    ///
+    /// # Examples
+    /// ```
+    /// use jsonbb::{Entry, Value};
+    ///
+    /// let value: Value = r#"{"a": 1}"#.parse().unwrap();
+    /// let entry = value.as_ref().entry();
+    /// assert_eq!(entry.tag(), Entry::OBJECT_TAG);
+    /// assert_eq!(entry.as_bytes(), &value.as_bytes()[value.as_bytes().len() - 4..]);
+    /// ```
+    pub fn entry(self) -> Entry {
+        self.make_entry(0)
+    }
+
     /// Returns the capacity to store this value, in bytes.
     pub fn capacity(self) -> usize {
         self.as_slice().len()
@@ -237,6 +376,66 @@ impl<'a> ValueRef<'a> {
         index.index_into(self)
     }
 
+    /// Index into a JSON array or object, failing if the value is of the wrong type.
+    ///
+    /// Returns [`Error::InvalidType`] if a usize index is used on a value other than an array,
+    /// or a string index on a value other than an object. Returns `Ok(None)` if the index is
+    /// out of bounds or the key is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// use jsonbb::{Error, JsonType, Value};
+    ///
+    /// let value: Value = r#"{"a": [1, 2]}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// assert_eq!(value.try_get("a").unwrap().unwrap().to_string(), "[1,2]");
+    /// assert!(value.try_get("b").unwrap().is_none());
+    /// assert!(matches!(
+    ///     value.try_get(0),
+    ///     Err(Error::InvalidType { expected: JsonType::Array, found: JsonType::Object })
+    /// ));
+    /// ```
+    pub fn try_get(self, index: impl Index) -> Result<Option<ValueRef<'a>>, Error> {
+        let expected = index.container_type();
+        if self.json_type() != expected {
+            return Err(Error::InvalidType {
+                expected,
+                found: self.json_type(),
+            });
+        }
+        Ok(index.index_into(self))
+    }
+
+    /// Looks up a value by a dotted path like `a.b.0.c`.
+    ///
+    /// Each segment is a key when the current value is an object, or an index when it is an
+    /// array. Indices are plain decimal numbers without a sign or leading zeros, as in
+    /// [`pointer`]. An empty path returns the value itself.
+    ///
+    /// Unlike [`pointer`], there is no escaping, so keys containing `.` can't be reached.
+    /// Prefer [`pointer`] for keys that are not under your control.
+    ///
+    /// [`pointer`]: #method.pointer
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": {"b": [{"c": 1}, {"c": 2}]}}"#.parse().unwrap();
+    /// let json = json.as_ref();
+    /// assert_eq!(json.get_dotted("a.b.1.c").unwrap().as_u64(), Some(2));
+    /// assert!(json.get_dotted("a.x").is_none());
+    /// ```
+    pub fn get_dotted(self, path: &str) -> Option<Self> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.')
+            .try_fold(self, |target, segment| match target {
+                Self::Object(map) => map.get(segment),
+                Self::Array(list) => list.get(parse_index(segment)?),
+                _ => None,
+            })
+    }
+
     /// Looks up a value by a JSON Pointer.
     pub fn pointer(self, pointer: &str) -> Option<Self> {
         if pointer.is_empty() {
@@ -246,13 +445,6 @@ impl<'a> ValueRef<'a> {
             return None;
         }
 
-        fn parse_index(s: &str) -> Option<usize> {
-            if s.starts_with('+') || (s.starts_with('0') && s.len() != 1) {
-                return None;
-            }
-            s.parse().ok()
-        }
-
         pointer
             .split('/')
             .skip(1)
@@ -263,17 +455,306 @@ impl<'a> ValueRef<'a> {
                 _ => None,
             })
     }
+
+    /// Serializes the value as compact JSON text, formatting floats with a fixed number of
+    /// decimal places.
+    ///
+    /// Only numbers stored as floats are affected. Integers and other values are serialized
+    /// as usual.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"[3.14159, 2, 0.5]"#.parse().unwrap();
+    /// assert_eq!(json.as_ref().to_string_with_float_precision(2), "[3.14,2,0.50]");
+    /// ```
+    pub fn to_string_with_float_precision(self, digits: usize) -> String {
+        use ::serde::Serialize;
+
+        struct PrecisionFormatter {
+            digits: usize,
+        }
+
+        impl serde_json::ser::Formatter for PrecisionFormatter {
+            fn write_f64<W: ?Sized + std::io::Write>(
+                &mut self,
+                writer: &mut W,
+                value: f64,
+            ) -> std::io::Result<()> {
+                write!(writer, "{:.*}", self.digits, value)
+            }
+        }
+
+        let mut buf = Vec::with_capacity(self.capacity());
+        let mut ser =
+            serde_json::Serializer::with_formatter(&mut buf, PrecisionFormatter { digits });
+        self.serialize(&mut ser)
+            .expect("serialize to vec should not fail");
+        // SAFETY: the serializer only emits valid utf8
+        unsafe { String::from_utf8_unchecked(buf) }
+    }
+
+    /// Serializes the value as pretty-printed JSON text.
+    ///
+    /// This is the same as `format!("{:#}", value)`. Members are indented by 2 spaces, and
+    /// there is no trailing newline.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": [1, null]}"#.parse().unwrap();
+    /// assert_eq!(json.as_ref().to_string_pretty(), "{\n  \"a\": [\n    1,\n    null\n  ]\n}");
+    /// ```
+    pub fn to_string_pretty(self) -> String {
+        format!("{self:#}")
+    }
+
+    /// Serializes the value as compact JSON text into a byte vector.
+    ///
+    /// This is the same as `value.to_string().into_bytes()`.
+    pub fn to_vec(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.capacity());
+        self.write_to(&mut buf);
+        buf
+    }
+
+    /// Serializes the value as pretty-printed JSON text into a byte vector.
+    ///
+    /// This is the same as `value.to_string_pretty().into_bytes()`.
+    pub fn to_vec_pretty(self) -> Vec<u8> {
+        self.to_string_pretty().into_bytes()
+    }
+
+    /// Appends the compact JSON text of the value to a byte buffer.
+    ///
+    /// This avoids allocating an intermediate `String`.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": [1, null]}"#.parse().unwrap();
+    /// let mut buf = b"data: ".to_vec();
+    /// json.as_ref().write_to(&mut buf);
+    /// assert_eq!(buf, br#"data: {"a":[1,null]}"#);
+    /// ```
+    pub fn write_to(self, out: &mut Vec<u8>) {
+        serde_json::to_writer(out, &self).expect("serialize to vec should not fail");
+    }
+
+    /// Serializes the value as compact JSON text into a `SmallVec`.
+    ///
+    /// Outputs of up to 64 bytes are stored inline without heap allocation, which makes this
+    /// cheaper than [`to_string`](ToString::to_string) for small values, e.g. when logging.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"ok": true}"#.parse().unwrap();
+    /// let bytes = json.as_ref().serialize_to_smallvec();
+    /// assert_eq!(&bytes[..], br#"{"ok":true}"#);
+    /// assert!(!bytes.spilled());
+    /// ```
+    pub fn serialize_to_smallvec(self) -> SmallVec<[u8; 64]> {
+        struct Writer(SmallVec<[u8; 64]>);
+
+        impl std::io::Write for Writer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = Writer(SmallVec::new());
+        serde_json::to_writer(&mut writer, &self).expect("serialize to smallvec should not fail");
+        writer.0
+    }
+
+    /// Returns the total number of values in the document, including containers and the
+    /// value itself. Object keys are not counted.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": [1, 2], "b": {"c": null}}"#.parse().unwrap();
+    /// assert_eq!(json.as_ref().count_nodes(), 6);
+    /// ```
+    pub fn count_nodes(self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            count += 1;
+            match value {
+                Self::Array(a) => stack.extend(a.iter()),
+                Self::Object(o) => stack.extend(o.values()),
+                _ => {}
+            }
+        }
+        count
+    }
+
+    /// Returns true if the two values have the same structure, ignoring scalar values.
+    ///
+    /// Scalars are structurally equal if they have the same type. Arrays are structurally equal
+    /// if they have the same length and their elements are structurally equal in order.
+    /// Objects are structurally equal if they have the same keys and the values for each key are
+    /// structurally equal.
+    ///
+    /// # Examples
+    /// ```
+    /// let a: jsonbb::Value = r#"{"id": 1, "tags": ["a"], "ok": true}"#.parse().unwrap();
+    /// let b: jsonbb::Value = r#"{"id": 2.5, "tags": ["b"], "ok": false}"#.parse().unwrap();
+    /// assert!(a.as_ref().structurally_eq(b.as_ref()));
+    ///
+    /// let c: jsonbb::Value = r#"{"id": 1, "tag": ["a"], "ok": true}"#.parse().unwrap();
+    /// assert!(!a.as_ref().structurally_eq(c.as_ref()));
+    ///
+    /// let d: jsonbb::Value = r#"{"id": "1", "tags": ["a"], "ok": true}"#.parse().unwrap();
+    /// assert!(!a.as_ref().structurally_eq(d.as_ref()));
+    /// ```
+    pub fn structurally_eq(self, other: ValueRef<'_>) -> bool {
+        match (self, other) {
+            (Self::Null, ValueRef::Null)
+            | (Self::Bool(_), ValueRef::Bool(_))
+            | (Self::Number(_), ValueRef::Number(_))
+            | (Self::String(_), ValueRef::String(_)) => true,
+            (Self::Array(a), ValueRef::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.structurally_eq(b))
+            }
+            (Self::Object(a), ValueRef::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.structurally_eq(vb))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if this value is contained in `other`, like the PostgreSQL `<@` operator.
+    ///
+    /// - An object is contained in another object if each of its keys is present in the other
+    ///   object and the value is contained in the corresponding value.
+    /// - An array is contained in another array if each of its elements is contained in some
+    ///   element of the other array, regardless of order and duplicates.
+    /// - A scalar is contained in an equal scalar. As a special exception, a scalar is also
+    ///   contained in an array that directly contains it, but only at the top level.
+    ///
+    /// # Examples
+    /// ```
+    /// let a: jsonbb::Value = r#"{"b": {"c": [1]}}"#.parse().unwrap();
+    /// let b: jsonbb::Value = r#"{"a": 0, "b": {"c": [2, 1], "d": 3}}"#.parse().unwrap();
+    /// assert!(a.as_ref().is_contained_in(b.as_ref()));
+    /// assert!(!b.as_ref().is_contained_in(a.as_ref()));
+    ///
+    /// let foo = jsonbb::Value::from("foo");
+    /// let array: jsonbb::Value = r#"["foo", "bar"]"#.parse().unwrap();
+    /// assert!(foo.as_ref().is_contained_in(array.as_ref()));
+    /// ```
+    pub fn is_contained_in(self, other: ValueRef<'_>) -> bool {
+        match (self, other) {
+            (Self::Array(_) | Self::Object(_), _) => self.is_contained_in_nested(other),
+            (_, ValueRef::Array(b)) => b.iter().any(|v| self == v),
+            _ => self == other,
+        }
+    }
+
+    /// Returns true if `other` matches this value as a pattern, allowing extra object keys.
+    ///
+    /// Objects in `other` must have every key of the corresponding object in `self` with a
+    /// matching value, but may have additional keys. Arrays must have the same length and match
+    /// element by element. Scalars must be equal. This is useful in tests to check that a value
+    /// contains the expected fields without listing all of them.
+    ///
+    /// Unlike [`is_contained_in`](Self::is_contained_in), the order and length of arrays matter.
+    ///
+    /// # Examples
+    /// ```
+    /// let pattern: jsonbb::Value = r#"{"items": [{"id": 1}, {"id": 2}]}"#.parse().unwrap();
+    /// let value: jsonbb::Value =
+    ///     r#"{"items": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}], "total": 2}"#
+    ///         .parse()
+    ///         .unwrap();
+    /// assert!(pattern.as_ref().eq_ignore_extra_keys(value.as_ref()));
+    /// assert!(!value.as_ref().eq_ignore_extra_keys(pattern.as_ref()));
+    /// ```
+    pub fn eq_ignore_extra_keys(self, other: ValueRef<'_>) -> bool {
+        match (self, other) {
+            (Self::Array(a), ValueRef::Array(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|(x, y)| x.eq_ignore_extra_keys(y))
+            }
+            (Self::Object(a), ValueRef::Object(b)) => a
+                .iter()
+                .all(|(k, v)| b.get(k).is_some_and(|w| v.eq_ignore_extra_keys(w))),
+            _ => self == other,
+        }
+    }
+
+    /// Containment check below the top level, where scalars never match arrays.
+    fn is_contained_in_nested(self, other: ValueRef<'_>) -> bool {
+        match (self, other) {
+            (Self::Array(a), ValueRef::Array(b)) => a
+                .iter()
+                .all(|x| b.iter().any(|y| x.is_contained_in_nested(y))),
+            (Self::Object(a), ValueRef::Object(b)) => a
+                .iter()
+                .all(|(k, v)| b.get(k).is_some_and(|w| v.is_contained_in_nested(w))),
+            _ => self == other,
+        }
+    }
+}
+
+/// Parses an array index in a path, rejecting a sign and leading zeros.
+fn parse_index(s: &str) -> Option<usize> {
+    if s.starts_with('+') || (s.starts_with('0') && s.len() != 1) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Compares two JSON values without taking ownership of them.
+///
+/// This is the same ordering as `Ord` for [`Value`] and [`ValueRef`], following PostgreSQL:
+///
+/// - Object > Array > Boolean > Number > String > Null
+/// - Arrays and objects with more elements or pairs are greater.
+/// - Otherwise, arrays are compared element by element, and objects are compared key by key
+///   followed by value by value in key order.
+///
+/// It can be passed to `sort_by` to sort borrowed values directly.
+///
+/// # Example
+///
+/// ```
+/// let value: jsonbb::Value = r#"[true, "a", null, 1]"#.parse().unwrap();
+/// let mut values: Vec<_> = value.as_array().unwrap().iter().collect();
+/// values.sort_by(|a, b| jsonbb::cmp_values(*a, *b));
+/// let sorted: Vec<_> = values.iter().map(|v| v.to_string()).collect();
+/// assert_eq!(sorted, ["null", r#""a""#, "1", "true"]);
+/// ```
+pub fn cmp_values(a: ValueRef<'_>, b: ValueRef<'_>) -> std::cmp::Ordering {
+    a.cmp(&b)
 }
 
+/// Debug a JSON value with the type of each node, e.g. `Object {"a": Number(i8: 1)}`.
+///
+/// Use `{:#?}` for a multi-line form. To format as JSON text, use `Display` instead.
 impl fmt::Debug for ValueRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Null => f.write_str("null"),
-            Self::Bool(b) => b.fmt(f),
+            Self::Null => f.write_str("Null"),
+            Self::Bool(b) => write!(f, "Bool({b})"),
             Self::Number(n) => n.fmt(f),
-            Self::String(s) => s.fmt(f),
-            Self::Array(a) => a.fmt(f),
-            Self::Object(o) => o.fmt(f),
+            Self::String(s) => write!(f, "String({s:?})"),
+            Self::Array(a) => {
+                f.write_str("Array ")?;
+                a.fmt(f)
+            }
+            Self::Object(o) => {
+                f.write_str("Object ")?;
+                o.fmt(f)
+            }
         }
     }
 }
@@ -374,9 +855,20 @@ impl NumberRef<'_> {
     }
 }
 
+/// Debug a JSON number with its storage type, e.g. `Number(i8: 1)` or `Number(f64: 1.0)`.
 impl fmt::Debug for NumberRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.to_number().fmt(f)
+        let ty = match self.data[0] {
+            NUMBER_ZERO => "zero",
+            NUMBER_I8 => "i8",
+            NUMBER_I16 => "i16",
+            NUMBER_I32 => "i32",
+            NUMBER_I64 => "i64",
+            NUMBER_U64 => "u64",
+            NUMBER_F64 => "f64",
+            t => panic!("invalid number tag: {t}"),
+        };
+        write!(f, "Number({ty}: {})", self.to_number())
     }
 }
 
@@ -388,23 +880,52 @@ impl fmt::Display for NumberRef<'_> {
 
 impl PartialEq for NumberRef<'_> {
     fn eq(&self, other: &Self) -> bool {
-        let a = self.to_number();
-        let b = other.to_number();
-        match (a.as_u64(), b.as_u64()) {
-            (Some(a), Some(b)) => return a == b,           // a, b > 0
-            (Some(_), None) if b.is_i64() => return false, // a >= 0 > b
-            (None, Some(_)) if a.is_i64() => return false, // a < 0 <= b
-            (None, None) => {
-                if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
-                    return a == b; // a, b < 0
-                }
-            }
-            _ => {}
+        number_eq(&self.to_number(), &other.to_number())
+    }
+}
+
+/// Compares two numbers by value, regardless of their representation.
+pub(crate) fn number_eq(a: &Number, b: &Number) -> bool {
+    number_cmp(a, b) == Ordering::Equal
+}
+
+/// Orders two numbers by value, regardless of their representation.
+///
+/// Integers and floats are compared exactly, without converting the integer to `f64`.
+/// Otherwise, e.g. `u64::MAX` and `u64::MAX - 1` would both be equal to `2^64` as floats.
+pub(crate) fn number_cmp(a: &Number, b: &Number) -> Ordering {
+    match (as_i128(a), as_i128(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(a), None) => cmp_i128_f64(a, b.as_f64().unwrap()),
+        (None, Some(b)) => cmp_i128_f64(b, a.as_f64().unwrap()).reverse(),
+        (None, None) => {
+            let a = a.as_f64().unwrap();
+            let b = b.as_f64().unwrap();
+            a.partial_cmp(&b).expect("NaN or Inf in JSON number")
         }
-        // either a or b is a float
-        let a = a.as_f64().unwrap();
-        let b = b.as_f64().unwrap();
-        a == b
+    }
+}
+
+/// Returns the value of an integer number.
+fn as_i128(n: &Number) -> Option<i128> {
+    n.as_u64()
+        .map(i128::from)
+        .or_else(|| n.as_i64().map(i128::from))
+}
+
+/// Compares an integer with a finite float exactly.
+fn cmp_i128_f64(i: i128, f: f64) -> Ordering {
+    // integers are within [-2^63, 2^64)
+    if f >= 18446744073709551616.0 {
+        return Ordering::Less;
+    }
+    if f < -9223372036854775808.0 {
+        return Ordering::Greater;
+    }
+    let t = f.trunc();
+    match i.cmp(&(t as i128)) {
+        Ordering::Equal => 0.0.partial_cmp(&(f - t)).unwrap(),
+        ord => ord,
     }
 }
 
@@ -417,33 +938,36 @@ impl PartialOrd for NumberRef<'_> {
 }
 
 impl Ord for NumberRef<'_> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let a = self.to_number();
-        let b = other.to_number();
-        match (a.as_u64(), b.as_u64()) {
-            (Some(a), Some(b)) => return a.cmp(&b), // a, b > 0
-            (Some(_), None) if b.is_i64() => return std::cmp::Ordering::Greater, // a >= 0 > b
-            (None, Some(_)) if a.is_i64() => return std::cmp::Ordering::Less, // a < 0 <= b
-            (None, None) => {
-                if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
-                    return a.cmp(&b); // a, b < 0
-                }
-            }
-            _ => {}
-        }
-        // either a or b is a float
-        let a = a.as_f64().unwrap();
-        let b = b.as_f64().unwrap();
-        a.partial_cmp(&b).expect("NaN or Inf in JSON number")
+    fn cmp(&self, other: &Self) -> Ordering {
+        number_cmp(&self.to_number(), &other.to_number())
     }
 }
 
 impl Hash for NumberRef<'_> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.to_number().hash(state);
+        // equal numbers must have the same hash, regardless of their representation
+        let n = self.to_number();
+        if let Some(i) = as_i128(&n) {
+            return i.hash(state);
+        }
+        let f = n.as_f64().unwrap();
+        if f.fract() == 0.0 && f.abs() < 18446744073709551616.0 {
+            (f as i128).hash(state);
+        } else {
+            f.to_bits().hash(state);
+        }
     }
 }
 
+/// The payload of an empty array or object: `len = 0` followed by `size = 8`.
+static EMPTY_CONTAINER: [u8; 8] = {
+    let len = 0u32.to_ne_bytes();
+    let size = 8u32.to_ne_bytes();
+    [
+        len[0], len[1], len[2], len[3], size[0], size[1], size[2], size[3],
+    ]
+};
+
 /// A reference to a JSON array.
 #[derive(Clone, Copy)]
 pub struct ArrayRef<'a> {
@@ -455,6 +979,15 @@ pub struct ArrayRef<'a> {
     data: &'a [u8],
 }
 
+impl ArrayRef<'static> {
+    /// Returns a reference to an empty array.
+    pub fn empty() -> Self {
+        Self {
+            data: &EMPTY_CONTAINER,
+        }
+    }
+}
+
 impl<'a> ArrayRef<'a> {
     /// Returns the element at the given index, or `None` if the index is out of bounds.
     pub fn get(self, index: usize) -> Option<ValueRef<'a>> {
@@ -468,6 +1001,8 @@ impl<'a> ArrayRef<'a> {
     }
 
     /// Returns the number of elements in the array.
+    ///
+    /// This is `O(1)`, as the length is stored in the footer of the array.
     pub fn len(self) -> usize {
         (&self.data[self.data.len() - 8..]).get_u32_ne() as usize
     }
@@ -478,12 +1013,74 @@ impl<'a> ArrayRef<'a> {
     }
 
     /// Returns an iterator over the array's elements.
-    pub fn iter(self) -> impl ExactSizeIterator<Item = ValueRef<'a>> {
+    pub fn iter(self) -> ArrayIter<'a> {
         let len = self.len();
         let offset = self.data.len() - 8 - 4 * len;
-        self.data[offset..offset + 4 * len]
-            .chunks_exact(4)
-            .map(|slice| ValueRef::from_slice(self.data, Entry::from(slice)))
+        ArrayIter {
+            data: self.data,
+            entries: self.data[offset..offset + 4 * len].chunks_exact(4),
+        }
+    }
+
+    /// Collects the elements into a `Vec<f64>`.
+    ///
+    /// Integers are converted to `f64`. Returns an error with the index of the first
+    /// element that is not a number.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"[1, 2.5, -3]"#.parse().unwrap();
+    /// let array = json.as_array().unwrap();
+    /// assert_eq!(array.as_vec_f64().unwrap(), [1.0, 2.5, -3.0]);
+    ///
+    /// let json: jsonbb::Value = r#"[1, "2", 3]"#.parse().unwrap();
+    /// let array = json.as_array().unwrap();
+    /// assert_eq!(array.as_vec_f64().unwrap_err().index(), 1);
+    /// ```
+    pub fn as_vec_f64(self) -> Result<Vec<f64>, ElementTypeError> {
+        self.collect_as("f64", ValueRef::as_f64)
+    }
+
+    /// Collects the elements into a `Vec<i64>`.
+    ///
+    /// Returns an error with the index of the first element that is not an integer
+    /// representable by `i64`.
+    pub fn as_vec_i64(self) -> Result<Vec<i64>, ElementTypeError> {
+        self.collect_as("i64", ValueRef::as_i64)
+    }
+
+    /// Collects the elements into a `Vec<u64>`.
+    ///
+    /// Returns an error with the index of the first element that is not an integer
+    /// representable by `u64`.
+    pub fn as_vec_u64(self) -> Result<Vec<u64>, ElementTypeError> {
+        self.collect_as("u64", ValueRef::as_u64)
+    }
+
+    /// Collects the elements into a `Vec<&str>`.
+    ///
+    /// Returns an error with the index of the first element that is not a string.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"["a", "b"]"#.parse().unwrap();
+    /// let array = json.as_array().unwrap();
+    /// assert_eq!(array.as_vec_str().unwrap(), ["a", "b"]);
+    /// ```
+    pub fn as_vec_str(self) -> Result<Vec<&'a str>, ElementTypeError> {
+        self.collect_as("string", ValueRef::as_str)
+    }
+
+    /// Collects the elements with `f`, failing on the first element it rejects.
+    fn collect_as<T>(
+        self,
+        expected: &'static str,
+        f: impl Fn(ValueRef<'a>) -> Option<T>,
+    ) -> Result<Vec<T>, ElementTypeError> {
+        self.iter()
+            .enumerate()
+            .map(|(index, v)| f(v).ok_or(ElementTypeError { index, expected }))
+            .collect()
     }
 
     /// Returns the entire array as a slice.
@@ -500,6 +1097,32 @@ impl<'a> ArrayRef<'a> {
     }
 }
 
+/// An error returned when an array element does not have the expected type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElementTypeError {
+    index: usize,
+    expected: &'static str,
+}
+
+impl ElementTypeError {
+    /// Returns the index of the offending element.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for ElementTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "array element at index {} is not of type {}",
+            self.index, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ElementTypeError {}
+
 impl fmt::Debug for ArrayRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.iter()).finish()
@@ -515,6 +1138,11 @@ impl fmt::Display for ArrayRef<'_> {
 
 impl PartialEq for ArrayRef<'_> {
     fn eq(&self, other: &Self) -> bool {
+        // Entries are relative to the start of the array, so equal bytes mean equal values.
+        // Otherwise fall back to comparing elements, e.g. for `1` and `1.0`.
+        if self.data == other.data {
+            return true;
+        }
         if self.len() != other.len() {
             return false;
         }
@@ -548,7 +1176,93 @@ impl Hash for ArrayRef<'_> {
     }
 }
 
+/// Iterate over the elements of an array.
+///
+/// # Example
+///
+/// ```
+/// let json: jsonbb::Value = r#"[1, 2, 3]"#.parse().unwrap();
+/// let mut sum = 0;
+/// for v in json.as_array().unwrap() {
+///     sum += v.as_u64().unwrap();
+/// }
+/// assert_eq!(sum, 6);
+/// ```
+impl<'a> IntoIterator for ArrayRef<'a> {
+    type Item = ValueRef<'a>;
+    type IntoIter = ArrayIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterate over the elements of an array.
+///
+/// # Example
+///
+/// ```
+/// let json: jsonbb::Value = r#"["a", "b"]"#.parse().unwrap();
+/// let array = json.as_array().unwrap();
+/// let mut strings = vec![];
+/// for v in &array {
+///     strings.push(v.as_str().unwrap());
+/// }
+/// assert_eq!(strings, ["a", "b"]);
+/// assert_eq!(array.len(), 2);
+/// ```
+impl<'a> IntoIterator for &ArrayRef<'a> {
+    type Item = ValueRef<'a>;
+    type IntoIter = ArrayIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of an array.
+///
+/// This is created by [`ArrayRef::iter`].
+#[derive(Clone)]
+pub struct ArrayIter<'a> {
+    data: &'a [u8],
+    entries: std::slice::ChunksExact<'a, u8>,
+}
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = ValueRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = self.entries.next()?;
+        Some(ValueRef::from_slice(self.data, Entry::from(slice)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ArrayIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let slice = self.entries.next_back()?;
+        Some(ValueRef::from_slice(self.data, Entry::from(slice)))
+    }
+}
+
+impl ExactSizeIterator for ArrayIter<'_> {}
+
+impl FusedIterator for ArrayIter<'_> {}
+
+impl fmt::Debug for ArrayIter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
 /// A reference to a JSON object.
+///
+/// Members are always stored sorted by key (byte-wise) and keys are unique, regardless of the
+/// order in which they were inserted. All iterators visit members in this sorted order.
 #[derive(Clone, Copy)]
 pub struct ObjectRef<'a> {
     // # layout
@@ -561,6 +1275,15 @@ pub struct ObjectRef<'a> {
     data: &'a [u8],
 }
 
+impl ObjectRef<'static> {
+    /// Returns a reference to an empty object.
+    pub fn empty() -> Self {
+        Self {
+            data: &EMPTY_CONTAINER,
+        }
+    }
+}
+
 impl<'a> ObjectRef<'a> {
     /// Returns the value associated with the given key, or `None` if the key is not present.
     ///
@@ -572,17 +1295,122 @@ impl<'a> ObjectRef<'a> {
     /// assert!(object.get("c").is_none());
     /// ```
     pub fn get(self, key: &str) -> Option<ValueRef<'a>> {
-        // do binary search since entries are ordered by key
+        let idx = self.binary_search(key).ok()?;
+        let (_, ventry) = self.entries()[idx];
+        Some(ValueRef::from_slice(self.data, ventry))
+    }
+
+    /// Returns the value of the first key that is equal to `key` ignoring ASCII case, or
+    /// `None` if there is no such key.
+    ///
+    /// Keys are sorted case-sensitively, so this is a linear scan over all members, which is
+    /// `O(n)` rather than the `O(log n)` of [`get`]. If several keys differ only by case, the
+    /// first one in sorted order is returned, which is not necessarily an exact match.
+    ///
+    /// [`get`]: #method.get
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"userId": 1, "name": "alice"}"#.parse().unwrap();
+    /// let object = json.as_object().unwrap();
+    /// assert_eq!(object.get_ignore_ascii_case("UserID").unwrap().as_u64(), Some(1));
+    /// assert!(object.get_ignore_ascii_case("user").is_none());
+    /// ```
+    pub fn get_ignore_ascii_case(self, key: &str) -> Option<ValueRef<'a>> {
+        self.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Returns the values associated with several keys at once.
+    ///
+    /// Keys are looked up in sorted order so that each search only covers the remaining
+    /// members, which is cheaper than separate calls to [`get`].
+    ///
+    /// [`get`]: #method.get
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": 1, "b": 2, "c": 3}"#.parse().unwrap();
+    /// let object = json.as_object().unwrap();
+    /// let [c, x, a] = object.get_many(["c", "x", "a"]);
+    /// assert_eq!(c.unwrap().as_u64(), Some(3));
+    /// assert!(x.is_none());
+    /// assert_eq!(a.unwrap().as_u64(), Some(1));
+    /// ```
+    pub fn get_many<const N: usize>(self, keys: [&str; N]) -> [Option<ValueRef<'a>>; N] {
         let entries = self.entries();
-        let idx = entries
-            .binary_search_by_key(&key, |&(kentry, _)| {
+        let mut order: [usize; N] = std::array::from_fn(|i| i);
+        order.sort_unstable_by_key(|&i| keys[i]);
+
+        let mut values = [None; N];
+        let mut start = 0;
+        for i in order {
+            let found = entries[start..].binary_search_by_key(&keys[i], |&(kentry, _)| {
                 ValueRef::from_slice(self.data, kentry)
                     .as_str()
                     .expect("key must be string")
-            })
-            .ok()?;
-        let (_, ventry) = entries[idx];
-        Some(ValueRef::from_slice(self.data, ventry))
+            });
+            match found {
+                Ok(idx) => {
+                    let (_, ventry) = entries[start + idx];
+                    values[i] = Some(ValueRef::from_slice(self.data, ventry));
+                    start += idx;
+                }
+                Err(idx) => start += idx,
+            }
+        }
+        values
+    }
+
+    /// Returns the value at a path of keys through nested objects.
+    ///
+    /// Returns `None` if any key is missing or any intermediate value is not an object.
+    /// An empty path returns the object itself.
+    /// Use [`ValueRef::pointer`] if the path also goes through arrays.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": {"b": {"c": 1}}}"#.parse().unwrap();
+    /// let object = json.as_object().unwrap();
+    /// assert_eq!(object.get_path(&["a", "b", "c"]).unwrap().as_u64(), Some(1));
+    /// assert!(object.get_path(&["a", "x", "c"]).is_none());
+    /// assert!(object.get_path(&["a", "b", "c", "d"]).is_none());
+    /// ```
+    pub fn get_path(self, keys: &[&str]) -> Option<ValueRef<'a>> {
+        let Some((last, init)) = keys.split_last() else {
+            return Some(ValueRef::Object(self));
+        };
+        let mut object = self;
+        for key in init {
+            object = object.get(key)?.as_object()?;
+        }
+        object.get(last)
+    }
+
+    /// Binary searches the object for the given key.
+    ///
+    /// If the key is found, returns `Ok` with the index of the member in iteration order.
+    /// Otherwise returns `Err` with the index where a member with this key could be inserted
+    /// while keeping the keys sorted.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"b": 1, "d": 2}"#.parse().unwrap();
+    /// let object = json.as_object().unwrap();
+    /// assert_eq!(object.binary_search("b"), Ok(0));
+    /// assert_eq!(object.binary_search("d"), Ok(1));
+    /// assert_eq!(object.binary_search("a"), Err(0));
+    /// assert_eq!(object.binary_search("c"), Err(1));
+    /// assert_eq!(object.binary_search("e"), Err(2));
+    /// ```
+    pub fn binary_search(self, key: &str) -> Result<usize, usize> {
+        // entries are ordered by key
+        self.entries().binary_search_by_key(&key, |&(kentry, _)| {
+            ValueRef::from_slice(self.data, kentry)
+                .as_str()
+                .expect("key must be string")
+        })
     }
 
     /// Returns `true` if the object contains a value for the specified key.
@@ -595,19 +1423,13 @@ impl<'a> ObjectRef<'a> {
     /// assert_eq!(object.contains_key("c"), false);
     /// ```
     pub fn contains_key(self, key: &str) -> bool {
-        // do binary search since entries are ordered by key
-        let entries = self.entries();
-        entries
-            .binary_search_by_key(&key, |&(kentry, _)| {
-                ValueRef::from_slice(self.data, kentry)
-                    .as_str()
-                    .expect("key must be string")
-            })
-            .is_ok()
+        self.binary_search(key).is_ok()
     }
 
     /// Returns the number of elements in the object.
     ///
+    /// This is `O(1)`, as the length is stored in the footer of the object.
+    ///
     /// # Examples
     /// ```
     /// let json: jsonbb::Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
@@ -630,7 +1452,7 @@ impl<'a> ObjectRef<'a> {
         self.len() == 0
     }
 
-    /// Returns an iterator over the object's key-value pairs.
+    /// Returns an iterator over the object's key-value pairs, sorted by key.
     ///
     /// # Examples
     /// ```
@@ -638,15 +1460,14 @@ impl<'a> ObjectRef<'a> {
     /// let kvs: Vec<_> = json.as_object().unwrap().iter().map(|(k, v)| (k, v.as_u64().unwrap())).collect();
     /// assert_eq!(kvs, [("a", 1), ("b", 2)]);
     /// ```
-    pub fn iter(self) -> impl ExactSizeIterator<Item = (&'a str, ValueRef<'a>)> {
-        self.entries().iter().map(move |&(kentry, ventry)| {
-            let k = ValueRef::from_slice(self.data, kentry);
-            let v = ValueRef::from_slice(self.data, ventry);
-            (k.as_str().expect("key must be string"), v)
-        })
+    pub fn iter(self) -> ObjectIter<'a> {
+        ObjectIter {
+            data: self.data,
+            entries: self.entries().iter(),
+        }
     }
 
-    /// Returns an iterator over the object's keys.
+    /// Returns an iterator over the object's keys, in sorted order.
     ///
     /// # Examples
     /// ```
@@ -658,7 +1479,7 @@ impl<'a> ObjectRef<'a> {
         self.iter().map(|(k, _)| k)
     }
 
-    /// Returns an iterator over the object's values.
+    /// Returns an iterator over the object's values, sorted by their keys.
     ///
     /// # Examples
     /// ```
@@ -707,6 +1528,11 @@ impl fmt::Display for ObjectRef<'_> {
 
 impl PartialEq for ObjectRef<'_> {
     fn eq(&self, other: &Self) -> bool {
+        // Entries are relative to the start of the object, so equal bytes mean equal values.
+        // Otherwise fall back to comparing members, e.g. for `1` and `1.0`.
+        if self.data == other.data {
+            return true;
+        }
         if self.len() != other.len() {
             return false;
         }
@@ -741,6 +1567,177 @@ impl Hash for ObjectRef<'_> {
     }
 }
 
+/// Iterate over the key-value pairs of an object, sorted by key.
+///
+/// # Example
+///
+/// ```
+/// let json: jsonbb::Value = r#"{"b": 2, "a": 1}"#.parse().unwrap();
+/// let mut keys = String::new();
+/// for (k, v) in json.as_object().unwrap() {
+///     keys.push_str(k);
+///     assert!(v.as_u64().is_some());
+/// }
+/// assert_eq!(keys, "ab");
+/// ```
+impl<'a> IntoIterator for ObjectRef<'a> {
+    type Item = (&'a str, ValueRef<'a>);
+    type IntoIter = ObjectIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterate over the key-value pairs of an object, sorted by key.
+///
+/// # Example
+///
+/// ```
+/// let json: jsonbb::Value = r#"{"b": 2, "a": 1}"#.parse().unwrap();
+/// let object = json.as_object().unwrap();
+/// let mut sum = 0;
+/// for (_, v) in &object {
+///     sum += v.as_u64().unwrap();
+/// }
+/// assert_eq!(sum, 3);
+/// assert_eq!(object.len(), 2);
+/// ```
+impl<'a> IntoIterator for &ObjectRef<'a> {
+    type Item = (&'a str, ValueRef<'a>);
+    type IntoIter = ObjectIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the key-value pairs of an object, sorted by key.
+///
+/// This is created by [`ObjectRef::iter`].
+#[derive(Clone)]
+pub struct ObjectIter<'a> {
+    data: &'a [u8],
+    entries: std::slice::Iter<'a, (Entry, Entry)>,
+}
+
+impl<'a> ObjectIter<'a> {
+    fn member(&self, (kentry, ventry): (Entry, Entry)) -> (&'a str, ValueRef<'a>) {
+        let k = ValueRef::from_slice(self.data, kentry);
+        let v = ValueRef::from_slice(self.data, ventry);
+        (k.as_str().expect("key must be string"), v)
+    }
+}
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (&'a str, ValueRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &entries = self.entries.next()?;
+        Some(self.member(entries))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ObjectIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let &entries = self.entries.next_back()?;
+        Some(self.member(entries))
+    }
+}
+
+impl ExactSizeIterator for ObjectIter<'_> {}
+
+impl FusedIterator for ObjectIter<'_> {}
+
+impl fmt::Debug for ObjectIter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.clone()).finish()
+    }
+}
+
+/// Checks that `entry` and everything it refers to in `data` is a valid encoding.
+///
+/// Containers are visited with an explicit stack, so deeply nested input can't overflow the
+/// call stack.
+fn validate(data: &[u8], entry: Entry) -> Result<(), &'static str> {
+    fn read_u32(data: &[u8], end: usize) -> Option<usize> {
+        let bytes = data.get(end.checked_sub(4)?..end)?;
+        Some(u32::from_ne_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    // every value has its own entry of 4 bytes, so a valid document has at most this many
+    // values. Entries sharing a child can't make the walk exceed it.
+    let mut budget = data.len() / 4;
+    let mut stack = vec![(data, entry)];
+    while let Some((data, entry)) = stack.pop() {
+        budget = budget.checked_sub(1).ok_or("overlapping values")?;
+        let ptr = entry.offset();
+        match entry.tag() {
+            Entry::NULL_TAG | Entry::FALSE_TAG | Entry::TRUE_TAG => {}
+            Entry::NUMBER_TAG => {
+                let tag = *data.get(ptr).ok_or("number out of bounds")?;
+                if ![
+                    NUMBER_ZERO,
+                    NUMBER_I8,
+                    NUMBER_I16,
+                    NUMBER_I32,
+                    NUMBER_I64,
+                    NUMBER_U64,
+                    NUMBER_F64,
+                ]
+                .contains(&tag)
+                {
+                    return Err("invalid number tag");
+                }
+                let payload = data
+                    .get(ptr + 1..ptr + 1 + number_size(tag))
+                    .ok_or("number out of bounds")?;
+                if tag == NUMBER_F64 && !f64::from_ne_bytes(payload.try_into().unwrap()).is_finite()
+                {
+                    return Err("non-finite number");
+                }
+            }
+            Entry::STRING_TAG => {
+                let len = read_u32(data, ptr + 4).ok_or("string out of bounds")?;
+                let payload = data
+                    .get(ptr + 4..)
+                    .and_then(|s| s.get(..len))
+                    .ok_or("string out of bounds")?;
+                std::str::from_utf8(payload).map_err(|_| "invalid utf-8 in string")?;
+            }
+            tag @ (Entry::ARRAY_TAG | Entry::OBJECT_TAG) => {
+                let size = read_u32(data, ptr).ok_or("container out of bounds")?;
+                if size < 8 || size > ptr {
+                    return Err("invalid container size");
+                }
+                let container = &data[ptr - size..ptr];
+                let len = read_u32(container, size - 4).unwrap();
+                let entry_size = if tag == Entry::ARRAY_TAG { 4 } else { 8 };
+                let entries = len
+                    .checked_mul(entry_size)
+                    .filter(|&n| n <= size - 8)
+                    .ok_or("invalid container length")?;
+                // children must lie in the payload before the entries, so that a child can't
+                // point back at its own container
+                let (payload, entries) = container[..size - 8].split_at(size - 8 - entries);
+                for (i, e) in entries.chunks_exact(4).enumerate() {
+                    let e = Entry::from(e);
+                    if tag == Entry::OBJECT_TAG && i % 2 == 0 && !e.is_string() {
+                        return Err("object key is not a string");
+                    }
+                    stack.push((payload, e));
+                }
+            }
+            _ => return Err("invalid entry tag"),
+        }
+    }
+    Ok(())
+}
+
 /// Serialize a value in JSON format.
 fn serialize_in_json(value: &impl ::serde::Serialize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     use std::io;
@@ -749,7 +1746,7 @@ fn serialize_in_json(value: &impl ::serde::Serialize, f: &mut fmt::Formatter<'_>
         inner: &'a mut fmt::Formatter<'b>,
     }
 
-    impl<'a, 'b> io::Write for WriterFormatter<'a, 'b> {
+    impl io::Write for WriterFormatter<'_, '_> {
         fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
             // Safety: the serializer below only emits valid utf8 when using
             // the default formatter.
@@ -766,7 +1763,7 @@ fn serialize_in_json(value: &impl ::serde::Serialize, f: &mut fmt::Formatter<'_>
     fn io_error(_: fmt::Error) -> io::Error {
         // Error value does not matter because Display impl just maps it
         // back to fmt::Error.
-        io::Error::new(io::ErrorKind::Other, "fmt error")
+        io::Error::other("fmt error")
     }
 
     let alternate = f.alternate();
@@ -789,6 +1786,10 @@ pub trait Index: private::Sealed {
     /// Return None if the key is not already in the array or object.
     #[doc(hidden)]
     fn index_into<'v>(&self, v: ValueRef<'v>) -> Option<ValueRef<'v>>;
+
+    /// Returns the type of value that can be indexed.
+    #[doc(hidden)]
+    fn container_type(&self) -> JsonType;
 }
 
 impl Index for usize {
@@ -798,6 +1799,10 @@ impl Index for usize {
             _ => None,
         }
     }
+
+    fn container_type(&self) -> JsonType {
+        JsonType::Array
+    }
 }
 
 impl Index for str {
@@ -807,6 +1812,10 @@ impl Index for str {
             _ => None,
         }
     }
+
+    fn container_type(&self) -> JsonType {
+        JsonType::Object
+    }
 }
 
 impl Index for String {
@@ -816,15 +1825,23 @@ impl Index for String {
             _ => None,
         }
     }
+
+    fn container_type(&self) -> JsonType {
+        JsonType::Object
+    }
 }
 
-impl<'a, T> Index for &'a T
+impl<T> Index for &T
 where
     T: ?Sized + Index,
 {
     fn index_into<'v>(&self, v: ValueRef<'v>) -> Option<ValueRef<'v>> {
         (**self).index_into(v)
     }
+
+    fn container_type(&self) -> JsonType {
+        (**self).container_type()
+    }
 }
 
 // Prevent users from implementing the Index trait.
@@ -833,5 +1850,5 @@ mod private {
     impl Sealed for usize {}
     impl Sealed for str {}
     impl Sealed for String {}
-    impl<'a, T> Sealed for &'a T where T: ?Sized + Sealed {}
+    impl<T> Sealed for &T where T: ?Sized + Sealed {}
 }
@@ -0,0 +1,259 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion between `Value` and JavaScript values with `wasm-bindgen`.
+//!
+//! Values are converted directly to and from JavaScript objects, without going through JSON text.
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::{JsCast, JsError, JsValue};
+
+use crate::{Builder, Value, ValueRef};
+
+/// The largest integer that a JavaScript number can represent exactly, i.e. `2^53 - 1`.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+impl Value {
+    /// Converts the value into a JavaScript value.
+    ///
+    /// See [`ValueRef::to_js`] for details.
+    pub fn to_js(&self) -> JsValue {
+        self.as_ref().to_js()
+    }
+
+    /// Converts a JavaScript value into a value.
+    ///
+    /// The conversion follows `JSON.stringify`, except that NaN, infinities, functions and
+    /// symbols are errors instead of being converted to `null` or dropped:
+    ///
+    /// - `undefined` in an object member is skipped, and in an array it becomes `null`, as
+    ///   with `JSON.stringify`. A top-level `undefined` is an error.
+    /// - Numbers that are integers within `±(2^53 - 1)` are stored as integers, and other
+    ///   numbers, including `-0`, as floats. NaN and infinities are errors.
+    /// - `BigInt` values are stored as integers. They are errors if they do not fit in `i64`
+    ///   or `u64`.
+    /// - Arrays are converted element by element. Other objects are converted from their own
+    ///   enumerable string-keyed properties.
+    /// - Functions, symbols and cyclic objects are errors.
+    pub fn from_js(value: &JsValue) -> Result<Self, JsError> {
+        if value.is_undefined() {
+            return Err(JsError::new("undefined is not a JSON value"));
+        }
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.add_js(value, &mut Vec::new())?;
+        Ok(builder.finish())
+    }
+}
+
+impl ValueRef<'_> {
+    /// Converts the value into a JavaScript value.
+    ///
+    /// Objects become plain objects and arrays become `Array`s. Integers beyond
+    /// `±(2^53 - 1)` become `BigInt`s so that they keep their exact value.
+    pub fn to_js(self) -> JsValue {
+        match self {
+            Self::Null => JsValue::NULL,
+            Self::Bool(b) => JsValue::from_bool(b),
+            Self::Number(n) => {
+                let n = n.to_number();
+                if let Some(i) = n.as_i64() {
+                    if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&i) {
+                        JsValue::from_f64(i as f64)
+                    } else {
+                        JsValue::from(i)
+                    }
+                } else if let Some(u) = n.as_u64() {
+                    JsValue::from(u)
+                } else {
+                    JsValue::from_f64(n.as_f64().unwrap())
+                }
+            }
+            Self::String(s) => JsValue::from_str(s),
+            Self::Array(a) => a.iter().map(|v| v.to_js()).collect::<Array>().into(),
+            Self::Object(o) => {
+                let object = Object::new();
+                for (k, v) in o.iter() {
+                    Reflect::set(&object, &JsValue::from_str(k), &v.to_js())
+                        .expect("failed to set property on a plain object");
+                }
+                object.into()
+            }
+        }
+    }
+}
+
+impl<W: AsMut<Vec<u8>>> Builder<W> {
+    /// Adds a JavaScript value to the builder.
+    ///
+    /// `ancestors` holds the arrays and objects being converted, to detect cycles.
+    fn add_js(&mut self, value: &JsValue, ancestors: &mut Vec<JsValue>) -> Result<(), JsError> {
+        if value.is_null() || value.is_undefined() {
+            self.add_null();
+        } else if let Some(b) = value.as_bool() {
+            self.add_bool(b);
+        } else if let Some(f) = value.as_f64() {
+            // -0 is kept as a float so that its sign is not lost
+            let is_neg_zero = f == 0.0 && f.is_sign_negative();
+            if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER as f64 && !is_neg_zero {
+                self.add_i64(f as i64);
+            } else {
+                self.try_add_f64(f)?;
+            }
+        } else if value.is_bigint() {
+            if let Ok(i) = i64::try_from(value.clone()) {
+                self.add_i64(i);
+            } else if let Ok(u) = u64::try_from(value.clone()) {
+                self.add_u64(u);
+            } else {
+                return Err(JsError::new(&format!(
+                    "BigInt {} is out of the range of i64 and u64",
+                    value
+                        .unchecked_ref::<js_sys::BigInt>()
+                        .to_string(10)
+                        .map(String::from)
+                        .unwrap_or_default()
+                )));
+            }
+        } else if let Some(s) = value.as_string() {
+            self.add_string(&s);
+        } else if value.is_function() || value.is_symbol() || !value.is_object() {
+            return Err(JsError::new(&format!(
+                "{} is not a JSON value",
+                value.js_typeof().as_string().unwrap_or_default()
+            )));
+        } else {
+            if ancestors.contains(value) {
+                return Err(JsError::new("cyclic object value"));
+            }
+            ancestors.push(value.clone());
+            if let Some(array) = value.dyn_ref::<Array>() {
+                self.begin_array();
+                for item in array.iter() {
+                    self.add_js(&item, ancestors)?;
+                }
+                self.end_array();
+            } else {
+                self.begin_object();
+                for entry in Object::entries(value.unchecked_ref()).iter() {
+                    let entry: Array = entry.unchecked_into();
+                    let item = entry.get(1);
+                    if item.is_undefined() {
+                        continue;
+                    }
+                    self.add_string(&entry.get(0).as_string().unwrap_or_default());
+                    self.add_js(&item, ancestors)?;
+                }
+                self.end_object();
+            }
+            ancestors.pop();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    fn parse_js(json: &str) -> JsValue {
+        js_sys::JSON::parse(json).unwrap()
+    }
+
+    fn stringify(value: &JsValue) -> String {
+        js_sys::JSON::stringify(value).unwrap().into()
+    }
+
+    #[wasm_bindgen_test]
+    fn roundtrip() {
+        let json = r#"{"a":[null,true,false,0,-1,1.5,"é"],"b":{"c":{}}}"#;
+        let value: Value = json.parse().unwrap();
+        let js = value.to_js();
+        assert_eq!(stringify(&js), json);
+        assert_eq!(Value::from_js(&js).unwrap(), value);
+        assert_eq!(Value::from_js(&parse_js(json)).unwrap(), value);
+
+        // integral numbers are stored as integers
+        let value = Value::from_js(&JsValue::from_f64(3.0)).unwrap();
+        assert_eq!(value.to_string(), "3");
+    }
+
+    #[wasm_bindgen_test]
+    fn bigint() {
+        let value: Value =
+            "[9007199254740991, 9007199254740992, -9223372036854775808, 18446744073709551615]"
+                .parse()
+                .unwrap();
+        let js: Array = value.to_js().unchecked_into();
+        assert!(js.get(0).as_f64().is_some());
+        assert_eq!(js.get(1), JsValue::from(1_i64 << 53));
+        assert_eq!(js.get(2), JsValue::from(i64::MIN));
+        assert_eq!(js.get(3), JsValue::from(u64::MAX));
+        assert_eq!(Value::from_js(&js).unwrap(), value);
+
+        let big = JsValue::from(u128::from(u64::MAX) + 1);
+        let err = JsValue::from(Value::from_js(&big).unwrap_err());
+        assert_eq!(
+            Reflect::get(&err, &"message".into()).unwrap(),
+            "BigInt 18446744073709551616 is out of the range of i64 and u64"
+        );
+
+        // numbers beyond 2^53 are not exact, and are stored as floats
+        let value = Value::from_js(&JsValue::from_f64(2f64.powi(60))).unwrap();
+        assert!(value.as_f64().is_some() && value.as_i64().is_none());
+
+        // -0 keeps its sign
+        let value = Value::from_js(&JsValue::from_f64(-0.0)).unwrap();
+        assert!(value.as_i64().is_none());
+        assert!(value.as_f64().unwrap().is_sign_negative());
+        assert_eq!(
+            Value::from_js(&JsValue::from_f64(0.0)).unwrap().as_i64(),
+            Some(0)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn undefined() {
+        assert!(Value::from_js(&JsValue::UNDEFINED).is_err());
+
+        let object = Object::new();
+        Reflect::set(&object, &"a".into(), &JsValue::UNDEFINED).unwrap();
+        Reflect::set(&object, &"b".into(), &Array::of1(&JsValue::UNDEFINED)).unwrap();
+        let value = Value::from_js(&object).unwrap();
+        assert_eq!(value.to_string(), r#"{"b":[null]}"#);
+    }
+
+    #[wasm_bindgen_test]
+    fn cyclic() {
+        let object = Object::new();
+        let array = Array::of1(&object);
+        Reflect::set(&object, &"a".into(), &array).unwrap();
+        assert!(Value::from_js(&object).is_err());
+
+        // shared but acyclic references are fine
+        let shared = parse_js(r#"{"x":1}"#);
+        let array = Array::of2(&shared, &shared);
+        let value = Value::from_js(&array).unwrap();
+        assert_eq!(value.to_string(), r#"[{"x":1},{"x":1}]"#);
+    }
+
+    #[wasm_bindgen_test]
+    fn invalid() {
+        assert!(Value::from_js(&JsValue::from_f64(f64::NAN)).is_err());
+        assert!(Value::from_js(&JsValue::symbol(None)).is_err());
+        let function = js_sys::Function::new_no_args("");
+        assert!(Value::from_js(&Array::of1(&function)).is_err());
+    }
+}